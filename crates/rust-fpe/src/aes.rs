@@ -1,61 +1,132 @@
 use crate::error::Error;
 use crate::result::Result;
 
+// pulls in the `aes`, `aria`, `camellia`, and `sm4` RustCrypto crates;
+// this source tree ships without a Cargo.toml, so all four need to be
+// added to the manifest alongside this module.
 use aes;
-use cbc;
+use aria;
+use camellia;
+use sm4;
 
-use aes::cipher::BlockEncryptMut;
+use aes::cipher::BlockEncrypt;
 use aes::cipher::BlockSizeUser;
-use aes::cipher::KeyIvInit;
+use aes::cipher::KeyInit;
+
+/// Which 128-bit block cipher backs an [`FFX`](crate::ffx::FFX)
+/// instance's PRF.
+///
+/// FFX only needs a keyed 128-bit-block cipher/PRF primitive, so
+/// nothing about the FF1/FF3-1 math changes based on this choice.
+/// Callers in jurisdictions that mandate a regional cipher (ARIA in
+/// Korea, SM4 in China) can pick it here while reusing the same
+/// driver code; everyone else should stick with the default, `Aes`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CipherKind {
+    Aes,
+    Aria,
+    Camellia,
+    /// SM4 only supports a 128-bit key.
+    Sm4,
+}
 
 #[derive(Clone)]
-enum CbcType {
-    Aes128(cbc::Encryptor<aes::Aes128>),
-    Aes192(cbc::Encryptor<aes::Aes192>),
-    Aes256(cbc::Encryptor<aes::Aes256>),
+enum BlockType {
+    Aes128(aes::Aes128),
+    Aes192(aes::Aes192),
+    Aes256(aes::Aes256),
+    Aria128(aria::Aria128),
+    Aria192(aria::Aria192),
+    Aria256(aria::Aria256),
+    Camellia128(camellia::Camellia128),
+    Camellia192(camellia::Camellia192),
+    Camellia256(camellia::Camellia256),
+    Sm4(sm4::Sm4),
 }
 
+/// A keyed 128-bit block cipher, used by [`FFX`](crate::ffx::FFX) as
+/// the PRF/`ciph` primitive.
+///
+/// This only ever performs single-block, out-of-place encryption
+/// (`encrypt_block`) with no chaining mode or IV of its own -
+/// `FfxContext::prf_into` does the CBC-MAC chaining explicitly. That
+/// keeps `Cipher` immutable and key-schedule-only, so it (and
+/// therefore `FFX`/`FF1`/`FF3_1`) is `Send + Sync` and can be shared
+/// across threads, e.g. wrapped in an `Arc`, without re-expanding the
+/// key schedule per thread.
 #[derive(Clone)]
 pub struct Cipher {
-    enc: CbcType,
+    block: BlockType,
     blksz: usize,
 }
 
 macro_rules! construct_cipher {
-    ($type:ident, $key:expr, $iv:expr) => {
+    ($module:ident, $variant:ident, $type:ident, $key:expr) => {
         Cipher {
-            blksz: aes::$type::block_size(),
-            enc: CbcType::$type(cbc::Encryptor::<aes::$type>::new(
-                $key.into(),
-                $iv.into(),
-            )),
+            blksz: $module::$type::block_size(),
+            block: BlockType::$variant($module::$type::new($key.into())),
         }
     };
 }
 
 impl Cipher {
+    /// Build a cipher using the default backend, AES. Equivalent to
+    /// `Cipher::new_with_kind(CipherKind::Aes, key)`.
     pub fn new(key: &[u8]) -> Result<Cipher> {
-        const IV: &[u8] = &[0u8; 16];
+        Cipher::new_with_kind(CipherKind::Aes, key)
+    }
 
-        Ok(match key.len() {
-            16 => construct_cipher!(Aes128, key, IV),
-            24 => construct_cipher!(Aes192, key, IV),
-            32 => construct_cipher!(Aes256, key, IV),
+    /// Build a cipher using the given backend and key. The accepted
+    /// key lengths depend on `kind`: AES, ARIA, and Camellia accept
+    /// 16/24/32-byte keys; SM4 only accepts a 16-byte key.
+    pub fn new_with_kind(kind: CipherKind, key: &[u8]) -> Result<Cipher> {
+        Ok(match (kind, key.len()) {
+            (CipherKind::Aes, 16) => construct_cipher!(aes, Aes128, Aes128, key),
+            (CipherKind::Aes, 24) => construct_cipher!(aes, Aes192, Aes192, key),
+            (CipherKind::Aes, 32) => construct_cipher!(aes, Aes256, Aes256, key),
+            (CipherKind::Aria, 16) => {
+                construct_cipher!(aria, Aria128, Aria128, key)
+            }
+            (CipherKind::Aria, 24) => {
+                construct_cipher!(aria, Aria192, Aria192, key)
+            }
+            (CipherKind::Aria, 32) => {
+                construct_cipher!(aria, Aria256, Aria256, key)
+            }
+            (CipherKind::Camellia, 16) => {
+                construct_cipher!(camellia, Camellia128, Camellia128, key)
+            }
+            (CipherKind::Camellia, 24) => {
+                construct_cipher!(camellia, Camellia192, Camellia192, key)
+            }
+            (CipherKind::Camellia, 32) => {
+                construct_cipher!(camellia, Camellia256, Camellia256, key)
+            }
+            (CipherKind::Sm4, 16) => construct_cipher!(sm4, Sm4, Sm4, key),
             _ => return Err(Error::new("invalid key length")),
         })
     }
 
-    pub fn encrypt_block(&mut self, src: &[u8], dst: &mut [u8]) {
-        match &mut self.enc {
-            CbcType::Aes128(e) => {
-                e.encrypt_block_b2b_mut(src.into(), dst.into())
+    /// Encrypt a single block out-of-place. `src` and `dst` must each
+    /// be exactly `block_size()` bytes.
+    pub fn encrypt_block(&self, src: &[u8], dst: &mut [u8]) {
+        match &self.block {
+            BlockType::Aes128(c) => c.encrypt_block_b2b(src.into(), dst.into()),
+            BlockType::Aes192(c) => c.encrypt_block_b2b(src.into(), dst.into()),
+            BlockType::Aes256(c) => c.encrypt_block_b2b(src.into(), dst.into()),
+            BlockType::Aria128(c) => c.encrypt_block_b2b(src.into(), dst.into()),
+            BlockType::Aria192(c) => c.encrypt_block_b2b(src.into(), dst.into()),
+            BlockType::Aria256(c) => c.encrypt_block_b2b(src.into(), dst.into()),
+            BlockType::Camellia128(c) => {
+                c.encrypt_block_b2b(src.into(), dst.into())
             }
-            CbcType::Aes192(e) => {
-                e.encrypt_block_b2b_mut(src.into(), dst.into())
+            BlockType::Camellia192(c) => {
+                c.encrypt_block_b2b(src.into(), dst.into())
             }
-            CbcType::Aes256(e) => {
-                e.encrypt_block_b2b_mut(src.into(), dst.into())
+            BlockType::Camellia256(c) => {
+                c.encrypt_block_b2b(src.into(), dst.into())
             }
+            BlockType::Sm4(c) => c.encrypt_block_b2b(src.into(), dst.into()),
         }
     }
 
@@ -1,17 +1,26 @@
 use crate::error::Error;
 use crate::result::Result;
 
+use std::collections::HashMap;
+
 const DEFAULT_ALPHABET: &str =
     "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
 
-struct Letter {
-    val: char,
-    pos: usize,
-}
+// number of codepoints covered by the direct-indexed ASCII fast path;
+// 256 so FF1's radix-256 byte-identity alphabet (`FF1::new_bytes`,
+// covering byte values 0-255) hits this path too, instead of falling
+// through to the `non_ascii` HashMap for the upper half of the range
+const ASCII_TABLE_LEN: usize = 256;
 
 pub struct Alphabet {
     by_pos: Vec<char>,
-    by_ltr: Vec<Letter>,
+    // direct lookup table for the common case of an ASCII alphabet:
+    // ascii_table[c as usize] holds the numeral position of `c`, or -1
+    // if `c` isn't in the alphabet. turns the per-character cost of
+    // `ltr` from O(log n) into a single array index.
+    ascii_table: [i32; ASCII_TABLE_LEN],
+    // fallback for alphabets containing non-ASCII symbols
+    non_ascii: HashMap<char, usize>,
 }
 
 impl Alphabet {
@@ -39,24 +48,28 @@ impl Alphabet {
             return Err(Error::new("not enough letters in alphabet"));
         }
 
-        let mut by_ltr = Vec::<Letter>::with_capacity(by_pos.len());
-        for c in &by_pos {
-            by_ltr.push(Letter {
-                val: *c,
-                pos: by_ltr.len(),
-            });
+        let mut sorted = by_pos.clone();
+        sorted.sort();
+        for i in 1..sorted.len() {
+            if sorted[i] == sorted[i - 1] {
+                return Err(Error::new("duplicate letter(s) in alphabet"));
+            }
         }
-        by_ltr.sort_by_key(|l| l.val);
 
-        for i in 1..by_ltr.len() {
-            if by_ltr[i].val == by_ltr[i - 1].val {
-                return Err(Error::new("duplicate letter(s) in alphabet"));
+        let mut ascii_table = [-1i32; ASCII_TABLE_LEN];
+        let mut non_ascii = HashMap::new();
+        for (pos, c) in by_pos.iter().enumerate() {
+            if (*c as u32 as usize) < ASCII_TABLE_LEN {
+                ascii_table[*c as usize] = pos as i32;
+            } else {
+                non_ascii.insert(*c, pos);
             }
         }
 
         Ok(Alphabet {
-            by_ltr: by_ltr,
-            by_pos: by_pos,
+            by_pos,
+            ascii_table,
+            non_ascii,
         })
     }
 
@@ -65,9 +78,18 @@ impl Alphabet {
     }
 
     pub fn ltr(&self, c: char) -> Result<usize> {
-        match self.by_ltr.binary_search_by_key(&c, |l| l.val) {
-            Ok(i) => Ok(self.by_ltr[i].pos),
-            Err(_) => {
+        if (c as u32 as usize) < ASCII_TABLE_LEN {
+            let pos = self.ascii_table[c as usize];
+            return if pos >= 0 {
+                Ok(pos as usize)
+            } else {
+                Err(Error::new(&format!("'{}' not found in alphabet", c)))
+            };
+        }
+
+        match self.non_ascii.get(&c) {
+            Some(pos) => Ok(*pos),
+            None => {
                 Err(Error::new(&format!("'{}' not found in alphabet", c)))
             }
         }
@@ -113,6 +135,24 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn ascii_lookup_roundtrip() -> Result<()> {
+        let alpha = Alphabet::new(None, None)?;
+        for (pos, c) in super::DEFAULT_ALPHABET.chars().enumerate() {
+            assert_eq!(alpha.ltr(c)?, pos);
+            assert_eq!(alpha.pos(pos)?, c);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn non_ascii_lookup() -> Result<()> {
+        let alpha = Alphabet::new(Some("αβγδ"), None)?;
+        assert_eq!(alpha.ltr('γ')?, 2);
+        assert!(alpha.ltr('ζ').is_err());
+        Ok(())
+    }
+
     #[test]
     fn letter_not_found() -> Result<()> {
         let alpha = Alphabet::new(None, None)?;
@@ -1,5 +1,12 @@
 //! The FF1 algorithm
 //!
+//! FF1, alongside FF3-1, is one of the two format-preserving
+//! encryption modes specified by NIST SP 800-38G. Unlike FF3-1, FF1
+//! supports arbitrary-length tweaks (including an empty one) and
+//! message lengths up to 2**32, which makes it the better fit for
+//! fields where FF3-1's 56-bit tweak and short maximum length are
+//! limiting.
+//!
 //! The FF1 algorithm supports key sizes of 128, 192, and 256 bits.
 //! The (maximum possible) length of the tweak is supplied by the
 //! caller and is essentially unbounded.
@@ -9,12 +16,120 @@
 //! parameters related to the algorithm. Once, this structure has
 //! been created, it can be used to encrypt and decrypt data
 
+use crate::error::Error;
 use crate::ffx;
 use crate::result::Result;
 
 use byteorder::ByteOrder;
 use num_traits::Euclid;
 
+// width, in bytes, of the fixed-size limb used by the radix-2 fast
+// path below; each half of the input must fit within this many bits
+const BINARY_LIMB_BYTES: usize = 16;
+const BINARY_LIMB_BITS: usize = BINARY_LIMB_BYTES * 8;
+
+fn chars_to_digits(ffx: &ffx::FFX, chars: &[char]) -> Result<Vec<u8>> {
+    let mut digits = Vec::with_capacity(chars.len());
+    for c in chars {
+        digits.push(ffx.alpha_ltr(*c)? as u8);
+    }
+    Ok(digits)
+}
+
+fn digits_to_chars(ffx: &ffx::FFX, digits: &[u8]) -> Result<Vec<char>> {
+    let mut chars = Vec::with_capacity(digits.len());
+    for &d in digits {
+        chars.push(ffx.alpha_pos(d as usize)?);
+    }
+    Ok(chars)
+}
+
+fn bits_to_limb(
+    ffx: &ffx::FFX,
+    bits: &[char],
+) -> Result<[u8; BINARY_LIMB_BYTES]> {
+    let mut out = [0u8; BINARY_LIMB_BYTES];
+    let nbits = bits.len();
+
+    for (i, c) in bits.iter().enumerate() {
+        let bit = ffx.alpha_ltr(*c)?;
+        let bitpos = nbits - 1 - i;
+        let byte = BINARY_LIMB_BYTES - 1 - bitpos / 8;
+        out[byte] |= (bit as u8) << (bitpos % 8);
+    }
+
+    Ok(out)
+}
+
+fn limb_to_bits(
+    ffx: &ffx::FFX,
+    limb: &[u8; BINARY_LIMB_BYTES],
+    nbits: usize,
+) -> Result<Vec<char>> {
+    let mut out = Vec::with_capacity(nbits);
+
+    for i in 0..nbits {
+        let bitpos = nbits - 1 - i;
+        let byte = BINARY_LIMB_BYTES - 1 - bitpos / 8;
+        let bit = (limb[byte] >> (bitpos % 8)) & 1;
+        out.push(ffx.alpha_pos(bit as usize)?);
+    }
+
+    Ok(out)
+}
+
+// add `y` (a big-endian byte slice, shorter than or equal to the limb
+// width) into `a` modulo 2**bits, with carry propagating from the
+// least significant byte up, mirroring how the generic path reduces
+// modulo radix**m
+fn add_mod_pow2(a: &mut [u8; BINARY_LIMB_BYTES], y: &[u8], bits: usize) {
+    let ylen = y.len();
+    let mut carry = 0u16;
+
+    for i in 0..BINARY_LIMB_BYTES {
+        let idx = BINARY_LIMB_BYTES - 1 - i;
+        let yb = if i < ylen { y[ylen - 1 - i] } else { 0 };
+        let sum = a[idx] as u16 + yb as u16 + carry;
+        a[idx] = sum as u8;
+        carry = sum >> 8;
+    }
+
+    mask_high_bits(a, bits);
+}
+
+fn sub_mod_pow2(a: &mut [u8; BINARY_LIMB_BYTES], y: &[u8], bits: usize) {
+    let ylen = y.len();
+    let mut borrow = 0i16;
+
+    for i in 0..BINARY_LIMB_BYTES {
+        let idx = BINARY_LIMB_BYTES - 1 - i;
+        let yb = if i < ylen { y[ylen - 1 - i] } else { 0 };
+        let diff = a[idx] as i16 - yb as i16 - borrow;
+        if diff < 0 {
+            a[idx] = (diff + 256) as u8;
+            borrow = 1;
+        } else {
+            a[idx] = diff as u8;
+            borrow = 0;
+        }
+    }
+
+    mask_high_bits(a, bits);
+}
+
+fn mask_high_bits(a: &mut [u8; BINARY_LIMB_BYTES], bits: usize) {
+    let keep_bytes = (bits + 7) / 8;
+    let rem = bits % 8;
+
+    for b in a.iter_mut().take(BINARY_LIMB_BYTES - keep_bytes) {
+        *b = 0;
+    }
+    if rem != 0 {
+        let idx = BINARY_LIMB_BYTES - keep_bytes;
+        a[idx] &= 0xFFu8 >> (8 - rem);
+    }
+}
+
 /// The FF1 context structure
 pub struct FF1 {
     ffx: ffx::FFX,
@@ -40,9 +155,33 @@ impl FF1 {
         maxtwk: usize,
         radix: usize,
         opt_alpha: Option<&str>,
+    ) -> Result<Self> {
+        Self::new_with_cipher(
+            crate::aes::CipherKind::Aes,
+            key,
+            opt_t,
+            mintwk,
+            maxtwk,
+            radix,
+            opt_alpha,
+        )
+    }
+
+    /// Same as [`FF1::new`], but backed by `kind` instead of always
+    /// AES. See [`crate::aes::CipherKind`] for the supported
+    /// backends.
+    pub fn new_with_cipher(
+        kind: crate::aes::CipherKind,
+        key: &[u8],
+        opt_t: Option<&[u8]>,
+        mintwk: usize,
+        maxtwk: usize,
+        radix: usize,
+        opt_alpha: Option<&str>,
     ) -> Result<Self> {
         Ok(FF1 {
-            ffx: ffx::FFX::new(
+            ffx: ffx::FFX::new_with_cipher(
+                kind,
                 key,
                 opt_t,
                 // the maximum input length allowed by the
@@ -56,6 +195,49 @@ impl FF1 {
         })
     }
 
+    /// Create a new FF1 context from a human passphrase instead of a
+    /// raw AES key.
+    ///
+    /// The key is derived from `passphrase` and `salt` using the KDF
+    /// configured in `kdf_params` (see [`crate::kdf`]). The same
+    /// `(passphrase, salt, kdf_params, key_len)` always derives the
+    /// same key, so `salt` must be saved alongside the ciphertext (it
+    /// need not be secret) for decryption to reproduce it.
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_passphrase(
+        passphrase: &[u8],
+        salt: &[u8],
+        key_len: usize,
+        kdf_params: &crate::kdf::KdfParams,
+        opt_t: Option<&[u8]>,
+        mintwk: usize,
+        maxtwk: usize,
+        radix: usize,
+        opt_alpha: Option<&str>,
+    ) -> Result<Self> {
+        let key =
+            crate::kdf::derive_key(passphrase, salt, key_len, kdf_params)?;
+        Self::new(&key, opt_t, mintwk, maxtwk, radix, opt_alpha)
+    }
+
+    /// Create a new FF1 context for encrypting raw bytes directly via
+    /// [`FF1::encrypt_bytes`]/[`FF1::decrypt_bytes`], instead of
+    /// `char` strings.
+    ///
+    /// This is plain `FF1::new` with `radix` fixed at 256 and the
+    /// alphabet fixed to the identity mapping (byte value `b` is
+    /// numeral position `b`), so callers don't need to build their
+    /// own 256-symbol alphabet string.
+    pub fn new_bytes(
+        key: &[u8],
+        opt_t: Option<&[u8]>,
+        mintwk: usize,
+        maxtwk: usize,
+    ) -> Result<Self> {
+        let alpha: String = (0u8..=255).map(|b| b as char).collect();
+        Self::new(key, opt_t, mintwk, maxtwk, 256, Some(&alpha))
+    }
+
     // the code wants to work with individual characters or letters.
     // this isn't possible with utf8, so the caller is expected to
     // convert Strings to sequences of chars
@@ -67,7 +249,6 @@ impl FF1 {
     ) -> Result<Vec<char>> {
         let ffx = &self.ffx;
         let radix = ffx.get_radix();
-        let blksz = ffx.get_cipher_block_size();
 
         let t = ffx.get_tweak(&opt_t);
         ffx.validate_tweak_length(t.len())?;
@@ -75,6 +256,49 @@ impl FF1 {
         let n = inp.len();
         ffx.validate_text_length(n)?;
 
+        // radix 2 (binary identifiers / bit strings) is common enough,
+        // and painful enough to route through num_bigint, that it gets
+        // a dedicated fast path operating on fixed-width byte buffers
+        // instead. it only kicks in while both halves fit in a single
+        // 128-bit limb; larger inputs fall back to the generic path
+        // below.
+        if radix == 2 && n - n / 2 <= BINARY_LIMB_BITS {
+            return self.cipher_chars_binary(inp, t, which);
+        }
+
+        // similarly, when the modulus on both Feistel halves fits in
+        // a fixed 256-bit limb array, skip num_bigint entirely: this
+        // is the overwhelmingly common case (10-30 digit numeric
+        // strings, surrogate keys, etc.) and the fixed-width limb
+        // path below avoids the BigInt allocations `cipher_chars`
+        // otherwise pays every round.
+        if radix > 2
+            && crate::limbs::fits(radix, n / 2)
+            && crate::limbs::fits(radix, n - n / 2)
+        {
+            return self.cipher_chars_limbs(inp, t, which);
+        }
+
+        self.cipher_chars_generic(inp, t, which)
+    }
+
+    // the generic, BigInt-backed Feistel implementation; always
+    // correct regardless of domain size, but pays a BigInt allocation
+    // per round. `cipher_chars` only falls through to this once the
+    // radix-2 and fixed-limb fast paths above have ruled themselves
+    // out.
+    fn cipher_chars_generic(
+        &self,
+        inp: &[char],
+        t: &[u8],
+        which: ffx::CipherType,
+    ) -> Result<Vec<char>> {
+        let ffx = &self.ffx;
+        let radix = ffx.get_radix();
+        let blksz = ffx.get_cipher_block_size();
+
+        let n = inp.len();
+
         // (step 1)
         let u = n / 2;
         let v = n - u;
@@ -246,6 +470,434 @@ impl FF1 {
         .concat())
     }
 
+    /// Number of scratch bytes [`FF1::encrypt_into`]/[`FF1::decrypt_into`]
+    /// need for an input of `n` characters and a tweak of `twklen`
+    /// bytes.
+    pub fn scratch_len(&self, n: usize, twklen: usize) -> usize {
+        let ffx = &self.ffx;
+        let radix = ffx.get_radix();
+        let blksz = ffx.get_cipher_block_size();
+
+        let v = n - n / 2;
+        let b =
+            ((((radix as f64).log2() * (v as f64)).ceil() as usize) + 7) / 8;
+        let d = 4 * ((b + 3) / 4) + 4;
+
+        let p_len =
+            16 + ((twklen + 1 + b + (blksz - 1)) / blksz) * blksz;
+        let r_len = ((d + (blksz - 1)) / blksz) * blksz;
+
+        p_len + r_len
+    }
+
+    /// Allocation-free variant of [`FF1::encrypt`].
+    ///
+    /// `scratch` must be at least [`FF1::scratch_len`] bytes (for the
+    /// given `inp.len()` and tweak length); it is reused in place of
+    /// the heap-allocated P/Q and R buffers `cipher_chars` otherwise
+    /// allocates on every call, so a caller that keeps `scratch`
+    /// around across calls (e.g. one per worker thread) can encrypt a
+    /// dataset with no per-call heap traffic beyond the BigInt
+    /// arithmetic. `out` must be exactly `inp.len()` characters.
+    pub fn encrypt_into(
+        &self,
+        inp: &[char],
+        out: &mut [char],
+        scratch: &mut [u8],
+        twk: Option<&[u8]>,
+    ) -> Result<()> {
+        self.cipher_chars_into(
+            inp,
+            out,
+            scratch,
+            twk,
+            ffx::CipherType::Encrypt,
+        )
+    }
+
+    /// Allocation-free variant of [`FF1::decrypt`]. See
+    /// [`FF1::encrypt_into`].
+    pub fn decrypt_into(
+        &self,
+        inp: &[char],
+        out: &mut [char],
+        scratch: &mut [u8],
+        twk: Option<&[u8]>,
+    ) -> Result<()> {
+        self.cipher_chars_into(
+            inp,
+            out,
+            scratch,
+            twk,
+            ffx::CipherType::Decrypt,
+        )
+    }
+
+    fn cipher_chars_into(
+        &self,
+        inp: &[char],
+        out: &mut [char],
+        scratch: &mut [u8],
+        opt_t: Option<&[u8]>,
+        which: ffx::CipherType,
+    ) -> Result<()> {
+        let ffx = &self.ffx;
+        let radix = ffx.get_radix();
+        let blksz = ffx.get_cipher_block_size();
+
+        let t = ffx.get_tweak(&opt_t);
+        ffx.validate_tweak_length(t.len())?;
+
+        let n = inp.len();
+        ffx.validate_text_length(n)?;
+
+        if out.len() != n {
+            return Err(crate::error::Error::new(&format!(
+                "output buffer length mismatch; expected {} characters, got {}",
+                n,
+                out.len()
+            )));
+        }
+
+        let u = n / 2;
+        let v = n - u;
+
+        let mut ctx = ffx.context();
+        let mut na = ctx.chars_to_bignum(&inp[..u])?;
+        let mut nb = ctx.chars_to_bignum(&inp[u..])?;
+
+        let b =
+            ((((radix as f64).log2() * (v as f64)).ceil() as usize) + 7) / 8;
+        let d = 4 * ((b + 3) / 4) + 4;
+
+        let p_len = 16 + ((t.len() + 1 + b + (blksz - 1)) / blksz) * blksz;
+        let r_len = ((d + (blksz - 1)) / blksz) * blksz;
+
+        if scratch.len() < p_len + r_len {
+            return Err(crate::error::Error::new(&format!(
+                "scratch buffer too small; need at least {} bytes, got {}",
+                p_len + r_len,
+                scratch.len()
+            )));
+        }
+
+        let (p, r) = scratch.split_at_mut(p_len);
+        let p = &mut p[..p_len];
+        let r = &mut r[..r_len];
+        p.fill(0);
+        r.fill(0);
+
+        p[0] = 1;
+        p[1] = 2;
+        byteorder::BigEndian::write_u32(&mut p[2..6], radix as u32);
+        p[2] = 1;
+        p[6] = 10;
+        p[7] = u as u8;
+        byteorder::BigEndian::write_u32(&mut p[8..12], n as u32);
+        byteorder::BigEndian::write_u32(&mut p[12..16], t.len() as u32);
+        {
+            let q = &mut p[16..];
+            q[0..t.len()].copy_from_slice(t);
+        }
+
+        let mut mu: num_bigint::BigInt = radix.into();
+        mu = mu.pow(u as u32);
+        let mut mv = mu.clone();
+        if u != v {
+            mv *= radix;
+        }
+
+        if let ffx::CipherType::Decrypt = which {
+            std::mem::swap(&mut na, &mut nb);
+            std::mem::swap(&mut mu, &mut mv);
+        }
+
+        for i in 0..10 {
+            {
+                let q = &mut p[16..];
+                let q_len = q.len();
+
+                match which {
+                    ffx::CipherType::Encrypt => q[q_len - b - 1] = i,
+                    ffx::CipherType::Decrypt => q[q_len - b - 1] = 9 - i,
+                }
+
+                let (_, mut v) = nb.to_bytes_le();
+                v.resize(b, 0);
+                v.reverse();
+                q[q_len - b..].copy_from_slice(&v);
+            }
+
+            ffx.prf(p, &mut r[..blksz])?;
+
+            for j in 1..r.len() / blksz {
+                let (s, rest) = r.split_at_mut(blksz);
+                let l = (j - 1) * blksz;
+
+                let w = byteorder::BigEndian::read_u32(&s[blksz - 4..]);
+                byteorder::BigEndian::write_u32(
+                    &mut s[blksz - 4..],
+                    w ^ j as u32,
+                );
+                ffx.ciph(s, &mut rest[l..l + blksz])?;
+                byteorder::BigEndian::write_u32(&mut s[blksz - 4..], w);
+            }
+
+            let y = num_bigint::BigInt::from_bytes_be(
+                num_bigint::Sign::Plus,
+                &r[..d],
+            );
+
+            match which {
+                ffx::CipherType::Encrypt => na += y,
+                ffx::CipherType::Decrypt => na -= y,
+            }
+            na = na.rem_euclid(&mu);
+            std::mem::swap(&mut mu, &mut mv);
+            std::mem::swap(&mut na, &mut nb);
+        }
+
+        if let ffx::CipherType::Decrypt = which {
+            std::mem::swap(&mut na, &mut nb);
+        }
+
+        let (out_a, out_b) = out.split_at_mut(u);
+        out_a.copy_from_slice(&ffx.bignum_to_chars(&na, Some(u))?);
+        out_b.copy_from_slice(&ffx.bignum_to_chars(&nb, Some(v))?);
+
+        Ok(())
+    }
+
+    // specialized path for domains that fit in a 256-bit fixed-width
+    // limb array: same P/Q block layout and PRF/ciph steps as the
+    // generic path, but A and B are `limbs::Limbs` instead of
+    // `num_bigint::BigInt`, so every round is plain u64 arithmetic
+    // with no heap allocation.
+    fn cipher_chars_limbs(
+        &self,
+        inp: &[char],
+        t: &[u8],
+        which: ffx::CipherType,
+    ) -> Result<Vec<char>> {
+        let ffx = &self.ffx;
+        let radix = ffx.get_radix();
+        let blksz = ffx.get_cipher_block_size();
+
+        let n = inp.len();
+        let u = n / 2;
+        let v = n - u;
+
+        let a_digits = chars_to_digits(ffx, &inp[..u])?;
+        let b_digits = chars_to_digits(ffx, &inp[u..])?;
+
+        let mut na = crate::limbs::from_digits_be(&a_digits, radix as u64);
+        let mut nb = crate::limbs::from_digits_be(&b_digits, radix as u64);
+
+        let b =
+            ((((radix as f64).log2() * (v as f64)).ceil() as usize) + 7) / 8;
+        let d = 4 * ((b + 3) / 4) + 4;
+
+        let mut p = vec![
+            0u8;
+            16 + ((t.len() + 1 + b + (blksz - 1)) / blksz) * blksz
+        ];
+        let mut r = vec![0u8; ((d + (blksz - 1)) / blksz) * blksz];
+
+        p[0] = 1;
+        p[1] = 2;
+        byteorder::BigEndian::write_u32(&mut p[2..6], radix as u32);
+        p[2] = 1;
+        p[6] = 10;
+        p[7] = u as u8;
+        byteorder::BigEndian::write_u32(&mut p[8..12], n as u32);
+        byteorder::BigEndian::write_u32(&mut p[12..16], t.len() as u32);
+        p[16..16 + t.len()].copy_from_slice(t);
+
+        let mut mu = crate::limbs::pow_small(radix as u64, u);
+        let mut mv = crate::limbs::pow_small(radix as u64, v);
+
+        if let ffx::CipherType::Decrypt = which {
+            std::mem::swap(&mut na, &mut nb);
+            std::mem::swap(&mut mu, &mut mv);
+        }
+
+        for i in 0..10 {
+            {
+                let q = &mut p[16..];
+                let q_len = q.len();
+
+                match which {
+                    ffx::CipherType::Encrypt => q[q_len - b - 1] = i,
+                    ffx::CipherType::Decrypt => q[q_len - b - 1] = 9 - i,
+                }
+
+                q[q_len - b..]
+                    .copy_from_slice(&crate::limbs::to_be_bytes(&nb, b));
+            }
+
+            ffx.prf(&p, &mut r[..blksz])?;
+
+            for j in 1..r.len() / blksz {
+                let (s, rest) = r.split_at_mut(blksz);
+                let l = (j - 1) * blksz;
+
+                let w = byteorder::BigEndian::read_u32(&s[blksz - 4..]);
+                byteorder::BigEndian::write_u32(
+                    &mut s[blksz - 4..],
+                    w ^ j as u32,
+                );
+                ffx.ciph(s, &mut rest[l..l + blksz])?;
+                byteorder::BigEndian::write_u32(&mut s[blksz - 4..], w);
+            }
+
+            // S is d bytes, and d reaches 36 near the `limbs::fits`
+            // cutoff, more than the 32 bytes the limbs hold: parse the
+            // leading 32 bytes directly, then fold any remaining bytes
+            // in one at a time, reducing modulo mu as we go
+            let head = d.min(crate::limbs::LIMB_BITS / 8);
+            let mut y = crate::limbs::from_be_bytes(&r[..head]);
+            y = crate::limbs::modulo(&y, &mu);
+            for &byte in &r[head..d] {
+                for _ in 0..8 {
+                    y = crate::limbs::modulo(&crate::limbs::add(&y, &y), &mu);
+                }
+                y = crate::limbs::add(&y, &crate::limbs::from_u64(byte as u64));
+                y = crate::limbs::modulo(&y, &mu);
+            }
+
+            match which {
+                ffx::CipherType::Encrypt => {
+                    na = crate::limbs::add(&na, &y);
+                }
+                ffx::CipherType::Decrypt => {
+                    // na and y are both already < mu, so add the
+                    // modulus once before subtracting to stay in the
+                    // unsigned domain, mirroring how `rem_euclid`
+                    // handles a negative intermediate value above
+                    na = crate::limbs::add(&na, &mu);
+                    na = crate::limbs::sub(&na, &y);
+                }
+            }
+            na = crate::limbs::modulo(&na, &mu);
+
+            std::mem::swap(&mut mu, &mut mv);
+            std::mem::swap(&mut na, &mut nb);
+        }
+
+        if let ffx::CipherType::Decrypt = which {
+            std::mem::swap(&mut na, &mut nb);
+        }
+
+        Ok([
+            digits_to_chars(
+                ffx,
+                &crate::limbs::to_digits_be(&na, radix as u64, u),
+            )?,
+            digits_to_chars(
+                ffx,
+                &crate::limbs::to_digits_be(&nb, radix as u64, v),
+            )?,
+        ]
+        .concat())
+    }
+
+    // specialized radix-2 path: A and B are bit strings, so every
+    // Feistel round is a carry-propagating add/sub mod a power of two
+    // over fixed-size byte buffers instead of a num_bigint operation.
+    // the PRF/ciph steps and the P/Q block layout are unchanged from
+    // the generic algorithm above; only the numeral<->integer
+    // conversion and the modular update change.
+    fn cipher_chars_binary(
+        &self,
+        inp: &[char],
+        t: &[u8],
+        which: ffx::CipherType,
+    ) -> Result<Vec<char>> {
+        let ffx = &self.ffx;
+        let blksz = ffx.get_cipher_block_size();
+
+        let n = inp.len();
+        let u = n / 2;
+        let v = n - u;
+
+        let mut a = bits_to_limb(ffx, &inp[..u])?;
+        let mut b = bits_to_limb(ffx, &inp[u..])?;
+
+        let b_len = (v + 7) / 8;
+        let d = 4 * ((b_len + 3) / 4) + 4;
+
+        let mut p = vec![
+            0u8;
+            16 + ((t.len() + 1 + b_len + (blksz - 1)) / blksz) * blksz
+        ];
+        let mut r = vec![0u8; ((d + (blksz - 1)) / blksz) * blksz];
+
+        p[0] = 1;
+        p[1] = 2;
+        byteorder::BigEndian::write_u32(&mut p[2..6], 2);
+        p[2] = 1;
+        p[6] = 10;
+        p[7] = u as u8;
+        byteorder::BigEndian::write_u32(&mut p[8..12], n as u32);
+        byteorder::BigEndian::write_u32(&mut p[12..16], t.len() as u32);
+        p[16..16 + t.len()].copy_from_slice(t);
+
+        let mut mu = u;
+        let mut mv = v;
+
+        if let ffx::CipherType::Decrypt = which {
+            std::mem::swap(&mut a, &mut b);
+            std::mem::swap(&mut mu, &mut mv);
+        }
+
+        for i in 0..10 {
+            {
+                let q = &mut p[16..];
+                let qlen = q.len();
+
+                match which {
+                    ffx::CipherType::Encrypt => q[qlen - b_len - 1] = i,
+                    ffx::CipherType::Decrypt => q[qlen - b_len - 1] = 9 - i,
+                }
+
+                q[qlen - b_len..]
+                    .copy_from_slice(&b[BINARY_LIMB_BYTES - b_len..]);
+            }
+
+            ffx.prf(&p, &mut r[..blksz])?;
+
+            for j in 1..r.len() / blksz {
+                let (s, rest) = r.split_at_mut(blksz);
+                let l = (j - 1) * blksz;
+
+                let w = byteorder::BigEndian::read_u32(&s[blksz - 4..]);
+                byteorder::BigEndian::write_u32(
+                    &mut s[blksz - 4..],
+                    w ^ j as u32,
+                );
+                ffx.ciph(s, &mut rest[l..l + blksz])?;
+                byteorder::BigEndian::write_u32(&mut s[blksz - 4..], w);
+            }
+
+            // y = NUM(S) mod 2**m; since the modulus is a power of two
+            // this is just the low m bits of the d-byte PRF output
+            let y = &r[d - ((mu + 7) / 8)..d];
+            match which {
+                ffx::CipherType::Encrypt => add_mod_pow2(&mut a, y, mu),
+                ffx::CipherType::Decrypt => sub_mod_pow2(&mut a, y, mu),
+            }
+
+            std::mem::swap(&mut mu, &mut mv);
+            std::mem::swap(&mut a, &mut b);
+        }
+
+        if let ffx::CipherType::Decrypt = which {
+            std::mem::swap(&mut a, &mut b);
+        }
+
+        Ok([limb_to_bits(ffx, &a, u)?, limb_to_bits(ffx, &b, v)?].concat())
+    }
+
     // common function to convert the input String to a sequence
     // of chars before the cipher operation and back again after
     fn cipher_string(
@@ -277,6 +929,77 @@ impl FF1 {
     pub fn decrypt(&self, ct: &str, twk: Option<&[u8]>) -> Result<String> {
         self.cipher_string(ct, twk, ffx::CipherType::Decrypt)
     }
+
+    /// Encrypt a string, binding the ciphertext to `associated_data`.
+    ///
+    /// The tweak is deterministically derived from `associated_data`
+    /// (e.g. a record id or field name) instead of being supplied
+    /// directly, so a caller can pass a domain string rather than
+    /// hand-building a tweak, and two records encrypted with
+    /// different associated data get independent permutations.
+    pub fn encrypt_with_context(
+        &self,
+        pt: &str,
+        associated_data: &[u8],
+    ) -> Result<String> {
+        let twk = self.ffx.derive_tweak(associated_data, 16)?;
+        self.encrypt(pt, Some(&twk))
+    }
+
+    /// Decrypt a string, deriving the tweak from `associated_data` as
+    /// in [`FF1::encrypt_with_context`]. `associated_data` must match
+    /// what was used during encryption.
+    pub fn decrypt_with_context(
+        &self,
+        ct: &str,
+        associated_data: &[u8],
+    ) -> Result<String> {
+        let twk = self.ffx.derive_tweak(associated_data, 16)?;
+        self.decrypt(ct, Some(&twk))
+    }
+
+    /// Encrypt a raw byte slice, as a convenience over converting to
+    /// and from `char`s by hand.
+    ///
+    /// The context must have been built with radix 256 (see
+    /// [`FF1::new_bytes`]); each byte is its own numeral, via the
+    /// identity mapping `new_bytes` sets up. This still goes through
+    /// the same `char`-based Feistel path as [`FF1::encrypt`], but
+    /// every byte value (0-255) hits `Alphabet`'s O(1) direct-indexed
+    /// table rather than its `HashMap` fallback.
+    pub fn encrypt_bytes(
+        &self,
+        pt: &[u8],
+        twk: Option<&[u8]>,
+    ) -> Result<Vec<u8>> {
+        self.cipher_bytes(pt, twk, ffx::CipherType::Encrypt)
+    }
+
+    /// Decrypt a raw byte slice produced by [`FF1::encrypt_bytes`].
+    pub fn decrypt_bytes(
+        &self,
+        ct: &[u8],
+        twk: Option<&[u8]>,
+    ) -> Result<Vec<u8>> {
+        self.cipher_bytes(ct, twk, ffx::CipherType::Decrypt)
+    }
+
+    fn cipher_bytes(
+        &self,
+        inp: &[u8],
+        opt_t: Option<&[u8]>,
+        which: ffx::CipherType,
+    ) -> Result<Vec<u8>> {
+        if self.ffx.get_radix() != 256 {
+            return Err(Error::new(
+                "encrypt_bytes/decrypt_bytes require a radix-256 context; build one with FF1::new_bytes",
+            ));
+        }
+
+        let inp_c: Vec<char> = inp.iter().map(|&b| b as char).collect();
+        let out_c = self.cipher_chars(&inp_c, opt_t, which)?;
+        Ok(out_c.into_iter().map(|c| c as u32 as u8).collect())
+    }
 }
 
 fn cipher(
@@ -312,4 +1035,159 @@ pub fn decrypt(
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::FF1;
+    use crate::result::Result;
+
+    #[test]
+    fn test_encrypt_with_context_roundtrip() -> Result<()> {
+        let key = [0u8; 16];
+        let ff1 = FF1::new(&key, None, 0, 0, 10, None)?;
+
+        let pt = "0123456789";
+        let ct = ff1.encrypt_with_context(pt, b"customer-42")?;
+        assert_eq!(ff1.decrypt_with_context(&ct, b"customer-42")?, pt);
+
+        let other_ct = ff1.encrypt_with_context(pt, b"customer-43")?;
+        assert_ne!(ct, other_ct);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_new_with_cipher_roundtrip() -> Result<()> {
+        let key = [0u8; 16];
+        let ff1 = FF1::new_with_cipher(
+            crate::aes::CipherKind::Camellia,
+            &key,
+            None,
+            0,
+            0,
+            10,
+            None,
+        )?;
+
+        let pt = "0123456789";
+        let ct = ff1.encrypt(pt, None)?;
+        assert_eq!(ff1.decrypt(&ct, None)?, pt);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_encrypt_bytes_roundtrip() -> Result<()> {
+        let key = [0u8; 16];
+        let ff1 = FF1::new_bytes(&key, None, 0, 0)?;
+
+        let pt: Vec<u8> = (0..20).collect();
+        let ct = ff1.encrypt_bytes(&pt, None)?;
+        assert_ne!(ct, pt);
+        assert_eq!(ff1.decrypt_bytes(&ct, None)?, pt);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_encrypt_bytes_requires_radix_256() -> Result<()> {
+        let key = [0u8; 16];
+        let ff1 = FF1::new(&key, None, 0, 0, 10, None)?;
+
+        assert!(ff1.encrypt_bytes(&[1, 2, 3], None).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_encrypt_into_matches_encrypt() -> Result<()> {
+        let key = [0u8; 16];
+        let ff1 = FF1::new(&key, None, 0, 0, 10, None)?;
+
+        let pt: Vec<char> = "0123456789".chars().collect();
+        let mut ct = vec!['\0'; pt.len()];
+        let mut scratch = vec![0u8; ff1.scratch_len(pt.len(), 0)];
+
+        ff1.encrypt_into(&pt, &mut ct, &mut scratch, None)?;
+        assert_eq!(
+            String::from_iter(ct.clone()),
+            ff1.encrypt(&String::from_iter(pt.clone()), None)?
+        );
+
+        let mut dt = vec!['\0'; pt.len()];
+        ff1.decrypt_into(&ct, &mut dt, &mut scratch, None)?;
+        assert_eq!(dt, pt);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_encrypt_into_rejects_undersized_scratch() -> Result<()> {
+        let key = [0u8; 16];
+        let ff1 = FF1::new(&key, None, 0, 0, 10, None)?;
+
+        let pt: Vec<char> = "0123456789".chars().collect();
+        let mut ct = vec!['\0'; pt.len()];
+        let mut scratch = vec![0u8; 1];
+
+        assert!(ff1.encrypt_into(&pt, &mut ct, &mut scratch, None).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_limb_path_roundtrip() -> Result<()> {
+        let key = [0u8; 32];
+        let ff1 = FF1::new(&key, None, 0, 0, 36, None)?;
+
+        let pt = "0123456789abcdefghi";
+        let ct = ff1.encrypt(pt, None)?;
+        assert_ne!(ct, pt);
+        assert_eq!(ff1.decrypt(&ct, None)?, pt);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_limb_path_matches_generic_near_boundary() -> Result<()> {
+        // radix 16, 63-character halves: log2(16)*63 == 252 bits, three
+        // bits under the tightened 255-bit `limbs::fits` cutoff.
+        // `cipher_chars_limbs` and `cipher_chars_generic` must agree
+        // here, confirming the fixed-width fast path stays correct
+        // right up against the boundary (see `limbs::fits`).
+        let key = [0u8; 32];
+        let ff1 = FF1::new(&key, None, 0, 0, 16, None)?;
+
+        let half: Vec<char> = "0123456789abcdef"
+            .chars()
+            .cycle()
+            .take(63)
+            .collect();
+        let pt: Vec<char> = half.iter().chain(half.iter()).copied().collect();
+        assert_eq!(pt.len(), 126);
+        assert!(crate::limbs::fits(16, 63));
+
+        let t: &[u8] = &[];
+        let fast = ff1.cipher_chars_limbs(&pt, t, crate::ffx::CipherType::Encrypt)?;
+        let generic =
+            ff1.cipher_chars_generic(&pt, t, crate::ffx::CipherType::Encrypt)?;
+
+        assert_eq!(fast, generic);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_radix2_roundtrip() -> Result<()> {
+        let key = [0u8; 16];
+        let ff1 = FF1::new(&key, None, 0, 0, 2, None)?;
+
+        let pt: String =
+            "0110100101111000010010111010011110000101101".to_string();
+        let ct = ff1.encrypt(&pt, None)?;
+        assert_ne!(ct, pt);
+
+        let dt = ff1.decrypt(&ct, None)?;
+        assert_eq!(dt, pt);
+
+        Ok(())
+    }
+}
@@ -30,14 +30,42 @@
 //! let out = ff3_1.decrypt(&ct, None).unwrap();
 //! assert!(out == pt);
 
+use crate::error::Error;
 use crate::ffx;
 use crate::result::Result;
 
 use num_traits::Euclid;
+use num_traits::ToPrimitive;
+
+// radix-2 (bitstring) inputs up to this many total bits are handled
+// by the native-integer fast path in `cipher_chars_binary`/
+// `encrypt_bits`/`decrypt_bits` below instead of `num_bigint`; longer
+// bitstrings fall back to the generic path.
+const BINARY_MAX_BITS: usize = 128;
+
+// the generic path reads A/B out of `inp` after reversing it (see the
+// comment in `cipher_chars`), which amounts to bit-reversing the
+// plain MSB-first value of each half before using it as a number.
+// this undoes that by reversing the low `bits` bits of `x`.
+fn reverse_bits(x: u128, bits: usize) -> u128 {
+    if bits == 0 {
+        return 0;
+    }
+    let mut out = 0u128;
+    for i in 0..bits {
+        out |= ((x >> i) & 1) << (bits - 1 - i);
+    }
+    out
+}
+
+/// The number of Feistel rounds fixed by the FF3-1 specification.
+/// See [`FF3_1::with_rounds`] to override it.
+const STANDARD_ROUNDS: usize = 8;
 
 /// The FF3_1 context structure
 pub struct FF3_1 {
     ffx: ffx::FFX,
+    rounds: usize,
 }
 
 impl FF3_1 {
@@ -59,13 +87,27 @@ impl FF3_1 {
         opt_twk: Option<&[u8]>,
         radix: usize,
         opt_alpha: Option<&str>,
+    ) -> Result<Self> {
+        Self::new_with_cipher(crate::aes::CipherKind::Aes, key, opt_twk, radix, opt_alpha)
+    }
+
+    /// Same as [`FF3_1::new`], but backed by `kind` instead of always
+    /// AES. See [`crate::aes::CipherKind`] for the supported
+    /// backends.
+    pub fn new_with_cipher(
+        kind: crate::aes::CipherKind,
+        key: &[u8],
+        opt_twk: Option<&[u8]>,
+        radix: usize,
+        opt_alpha: Option<&str>,
     ) -> Result<Self> {
         // key is reversed for ff3-1
         let mut k = key.to_vec();
         k.reverse();
 
         Ok(FF3_1 {
-            ffx: ffx::FFX::new(
+            ffx: ffx::FFX::new_with_cipher(
+                kind,
                 &k,
                 opt_twk,
                 // maxlen for ff3-1:
@@ -80,9 +122,48 @@ impl FF3_1 {
                 radix,
                 opt_alpha,
             )?,
+            rounds: STANDARD_ROUNDS,
         })
     }
 
+    /// Override the number of Feistel rounds (default
+    /// [`STANDARD_ROUNDS`], the value fixed by the FF3-1
+    /// specification).
+    ///
+    /// This is a research knob, not something a production deployment
+    /// should reach for: NIST SP 800-38G specifies exactly 8 rounds,
+    /// and any `n != 8` produces a construction that is not FF3-1 and
+    /// is incompatible with every other FF3-1 implementation,
+    /// including this crate's own default. A ciphertext produced with
+    /// a non-default round count can only be decrypted by a context
+    /// built with the same `with_rounds(n)`.
+    pub fn with_rounds(mut self, n: usize) -> Self {
+        self.rounds = n;
+        self
+    }
+
+    /// Create a new FF3-1 context from a human passphrase instead of a
+    /// raw AES key.
+    ///
+    /// The key is derived from `passphrase` and `salt` using the KDF
+    /// configured in `kdf_params` (see [`crate::kdf`]). The same
+    /// `(passphrase, salt, kdf_params, key_len)` always derives the
+    /// same key, so `salt` must be saved alongside the ciphertext (it
+    /// need not be secret) for decryption to reproduce it.
+    pub fn from_passphrase(
+        passphrase: &[u8],
+        salt: &[u8],
+        key_len: usize,
+        kdf_params: &crate::kdf::KdfParams,
+        opt_twk: Option<&[u8]>,
+        radix: usize,
+        opt_alpha: Option<&str>,
+    ) -> Result<Self> {
+        let key =
+            crate::kdf::derive_key(passphrase, salt, key_len, kdf_params)?;
+        Self::new(&key, opt_twk, radix, opt_alpha)
+    }
+
     // the code wants to work with individual characters or letters.
     // this isn't possible with utf8, so the caller is expected to
     // convert Strings to sequences of chars
@@ -91,6 +172,28 @@ impl FF3_1 {
         inp: &[char],
         opt_twk: Option<&[u8]>,
         which: ffx::CipherType,
+    ) -> Result<Vec<char>> {
+        let radix = self.ffx.get_radix();
+        let n = inp.len();
+
+        // radix 2 (bitstrings / binary identifiers) is common enough
+        // to warrant a fast path directly on native integers instead
+        // of num_bigint; see `cipher_chars_binary`. `cipher_chars_generic`
+        // below still handles it correctly (just slower), which is
+        // what lets the two be cross-checked against each other in
+        // tests.
+        if radix == 2 && n <= BINARY_MAX_BITS {
+            return self.cipher_chars_binary(inp, opt_twk, which);
+        }
+
+        self.cipher_chars_generic(inp, opt_twk, which)
+    }
+
+    fn cipher_chars_generic(
+        &self,
+        inp: &[char],
+        opt_twk: Option<&[u8]>,
+        which: ffx::CipherType,
     ) -> Result<Vec<char>> {
         let ffx = &self.ffx;
         let radix = ffx.get_radix();
@@ -154,14 +257,14 @@ impl FF3_1 {
             std::mem::swap(&mut t0[0], &mut t1[0]);
         }
 
-        for i in 0..8 {
+        for i in 0..self.rounds {
             let mut p: [[u8; 16]; 2] = [[0; 16]; 2];
 
             // (step 4i, 4ii)
             p[0][..4].copy_from_slice(&tw[((i + 1) as u8 % 2) as usize]);
             match which {
-                ffx::CipherType::Encrypt => p[0][3] ^= i,
-                ffx::CipherType::Decrypt => p[0][3] ^= 7 - i,
+                ffx::CipherType::Encrypt => p[0][3] ^= i as u8,
+                ffx::CipherType::Decrypt => p[0][3] ^= (self.rounds - 1 - i) as u8,
             }
 
             // the num_bigint library doesn't provide left padding,
@@ -219,6 +322,337 @@ impl FF3_1 {
         Ok([a, b].concat())
     }
 
+    /// Number of scratch `char`s [`FF3_1::encrypt_into`]/
+    /// [`FF3_1::decrypt_into`] need for an input of `n` characters.
+    ///
+    /// Unlike FF1, whose P/Q/R blocks are heap-allocated `Vec<u8>`,
+    /// FF3-1's per-round block (`p` in [`FF3_1::cipher_chars_generic`])
+    /// is already a fixed-size stack array. `scratch` reuses a
+    /// caller-supplied buffer in place of that path's two reversed-half
+    /// `Vec<char>`s (and the concatenated output); the BigInt
+    /// arithmetic and digit conversions still allocate.
+    pub fn scratch_len(&self, n: usize) -> usize {
+        n
+    }
+
+    /// Allocation-free variant of [`FF3_1::encrypt`].
+    ///
+    /// `scratch` must be at least [`FF3_1::scratch_len`] characters
+    /// (for the given `inp.len()`); it is reused in place of the
+    /// heap-allocated half buffers `cipher_chars` otherwise allocates
+    /// on every call, so a caller that keeps `scratch` around across
+    /// calls (e.g. one per worker thread) can encrypt a dataset with
+    /// no per-call heap traffic beyond the BigInt arithmetic. `out`
+    /// must be exactly `inp.len()` characters.
+    pub fn encrypt_into(
+        &self,
+        inp: &[char],
+        out: &mut [char],
+        scratch: &mut [char],
+        twk: Option<&[u8]>,
+    ) -> Result<()> {
+        self.cipher_chars_into(
+            inp,
+            out,
+            scratch,
+            twk,
+            ffx::CipherType::Encrypt,
+        )
+    }
+
+    /// Allocation-free variant of [`FF3_1::decrypt`]. See
+    /// [`FF3_1::encrypt_into`].
+    pub fn decrypt_into(
+        &self,
+        inp: &[char],
+        out: &mut [char],
+        scratch: &mut [char],
+        twk: Option<&[u8]>,
+    ) -> Result<()> {
+        self.cipher_chars_into(
+            inp,
+            out,
+            scratch,
+            twk,
+            ffx::CipherType::Decrypt,
+        )
+    }
+
+    fn cipher_chars_into(
+        &self,
+        inp: &[char],
+        out: &mut [char],
+        scratch: &mut [char],
+        opt_twk: Option<&[u8]>,
+        which: ffx::CipherType,
+    ) -> Result<()> {
+        let ffx = &self.ffx;
+        let radix = ffx.get_radix();
+
+        let n = inp.len();
+        ffx.validate_text_length(n)?;
+
+        if out.len() != n {
+            return Err(Error::new(&format!(
+                "output buffer length mismatch; expected {} characters, got {}",
+                n,
+                out.len()
+            )));
+        }
+        if scratch.len() < n {
+            return Err(Error::new(&format!(
+                "scratch buffer too small; need at least {} characters, got {}",
+                n,
+                scratch.len()
+            )));
+        }
+
+        let v = n / 2;
+        let u = n - v;
+
+        // fill `buf` with the reversed A||B halves directly, instead
+        // of allocating and reversing two separate `Vec<char>`s (see
+        // `cipher_chars_generic`)
+        let buf = &mut scratch[..n];
+        for i in 0..u {
+            buf[i] = inp[u - 1 - i];
+        }
+        for i in 0..v {
+            buf[u + i] = inp[n - 1 - i];
+        }
+
+        let t = ffx.get_tweak(&opt_twk);
+        ffx.validate_tweak_length(t.len())?;
+
+        let mut tw: [[u8; 4]; 2] = [[0; 4]; 2];
+        tw[0][..3].copy_from_slice(&t[..3]);
+        tw[0][3] = t[3] & 0xf0;
+        tw[1][..3].copy_from_slice(&t[4..]);
+        tw[1][3] = (t[3] & 0x0f) << 4;
+
+        let mut mv: num_bigint::BigInt = radix.into();
+        mv = mv.pow(v as u32);
+        let mut mu = mv.clone();
+        if v != u {
+            mu *= radix;
+        }
+
+        let mut ctx = ffx.context();
+        let mut na = ctx.chars_to_bignum(&buf[..u])?;
+        let mut nb = ctx.chars_to_bignum(&buf[u..])?;
+
+        if let ffx::CipherType::Decrypt = which {
+            std::mem::swap(&mut na, &mut nb);
+            std::mem::swap(&mut mu, &mut mv);
+
+            let (t0, t1) = tw.split_at_mut(1);
+            std::mem::swap(&mut t0[0], &mut t1[0]);
+        }
+
+        for i in 0..self.rounds {
+            let mut p: [[u8; 16]; 2] = [[0; 16]; 2];
+
+            p[0][..4].copy_from_slice(&tw[((i + 1) as u8 % 2) as usize]);
+            match which {
+                ffx::CipherType::Encrypt => p[0][3] ^= i as u8,
+                ffx::CipherType::Decrypt => p[0][3] ^= (self.rounds - 1 - i) as u8,
+            }
+
+            let (_, mut v) = nb.to_bytes_le();
+            v.resize(12, 0);
+            v.reverse();
+            p[0][4..16].copy_from_slice(&v);
+
+            p[0].reverse();
+            {
+                let (p0, p1) = p.split_at_mut(1);
+                ffx.ciph(&p0[0], &mut p1[0])?;
+            }
+            p[1].reverse();
+
+            let y = num_bigint::BigInt::from_bytes_be(
+                num_bigint::Sign::Plus,
+                &p[1],
+            );
+
+            match which {
+                ffx::CipherType::Encrypt => na += y,
+                ffx::CipherType::Decrypt => na -= y,
+            }
+            na = na.rem_euclid(&mu);
+            std::mem::swap(&mut mu, &mut mv);
+            std::mem::swap(&mut na, &mut nb);
+        }
+
+        if let ffx::CipherType::Decrypt = which {
+            std::mem::swap(&mut na, &mut nb);
+        }
+
+        let b_chars = ffx.bignum_to_chars(&nb, Some(v))?;
+        let a_chars = ffx.bignum_to_chars(&na, Some(u))?;
+
+        for i in 0..u {
+            out[i] = a_chars[u - 1 - i];
+        }
+        for i in 0..v {
+            out[u + i] = b_chars[v - 1 - i];
+        }
+
+        Ok(())
+    }
+
+    fn cipher_chars_binary(
+        &self,
+        inp: &[char],
+        opt_twk: Option<&[u8]>,
+        which: ffx::CipherType,
+    ) -> Result<Vec<char>> {
+        let ffx = &self.ffx;
+        let n = inp.len();
+
+        let mut bits = 0u128;
+        for c in inp {
+            bits = (bits << 1) | ffx.alpha_ltr(*c)? as u128;
+        }
+
+        let out = self.cipher_bits(bits, n, opt_twk, which)?;
+
+        let mut chars = Vec::with_capacity(n);
+        for i in (0..n).rev() {
+            chars.push(ffx.alpha_pos(((out >> i) & 1) as usize)?);
+        }
+        Ok(chars)
+    }
+
+    /// Core of the radix-2 fast path, shared by [`FF3_1::cipher_chars_binary`]
+    /// and the public [`FF3_1::encrypt_bits`]/[`FF3_1::decrypt_bits`]: the
+    /// same round structure as the generic [`FF3_1::cipher_chars_generic`], but with
+    /// `A`/`B`/the radix powers kept as plain `u128`s instead of
+    /// `num_bigint::BigInt`, and no `char`/digit-vector conversion at all.
+    fn cipher_bits(
+        &self,
+        bits: u128,
+        n: usize,
+        opt_twk: Option<&[u8]>,
+        which: ffx::CipherType,
+    ) -> Result<u128> {
+        let ffx = &self.ffx;
+        ffx.validate_text_length(n)?;
+        if ffx.get_radix() != 2 {
+            return Err(Error::new(
+                "encrypt_bits/decrypt_bits require a radix-2 context",
+            ));
+        }
+        if n > BINARY_MAX_BITS {
+            return Err(Error::new(&format!(
+                "bit length too large for the native-integer path; expected at most {}, got {}",
+                BINARY_MAX_BITS, n
+            )));
+        }
+
+        // (step 1)
+        let v = n / 2;
+        let u = n - v;
+
+        let a_mask = if u == 128 { u128::MAX } else { (1u128 << u) - 1 };
+        let b_mask = if v == 128 { u128::MAX } else { (1u128 << v) - 1 };
+
+        // (step 2) split into halves, then undo the reversal the
+        // generic path applies (see `reverse_bits`)
+        let mut na = reverse_bits((bits >> v) & a_mask, u);
+        let mut nb = reverse_bits(bits & b_mask, v);
+
+        let t = ffx.get_tweak(&opt_twk);
+        ffx.validate_tweak_length(t.len())?;
+
+        // (step 3)
+        let mut tw: [[u8; 4]; 2] = [[0; 4]; 2];
+        tw[0][..3].copy_from_slice(&t[..3]);
+        tw[0][3] = t[3] & 0xf0;
+        tw[1][..3].copy_from_slice(&t[4..]);
+        tw[1][3] = (t[3] & 0x0f) << 4;
+
+        // (step 4v, partial)
+        let mut mu = 1u128 << u;
+        let mut mv = 1u128 << v;
+
+        if let ffx::CipherType::Decrypt = which {
+            std::mem::swap(&mut na, &mut nb);
+            std::mem::swap(&mut mu, &mut mv);
+
+            let (t0, t1) = tw.split_at_mut(1);
+            std::mem::swap(&mut t0[0], &mut t1[0]);
+        }
+
+        for i in 0..self.rounds {
+            let mut p: [[u8; 16]; 2] = [[0; 16]; 2];
+
+            // (step 4i, 4ii)
+            p[0][..4].copy_from_slice(&tw[((i + 1) as u8 % 2) as usize]);
+            match which {
+                ffx::CipherType::Encrypt => p[0][3] ^= i as u8,
+                ffx::CipherType::Decrypt => p[0][3] ^= (self.rounds - 1 - i) as u8,
+            }
+
+            // nb's big-endian representation, right-aligned in the
+            // 12-byte tail of p[0]
+            p[0][4..16].copy_from_slice(&nb.to_be_bytes()[4..16]);
+
+            p[0].reverse();
+            {
+                let (p0, p1) = p.split_at_mut(1);
+                ffx.ciph(&p0[0], &mut p1[0])?;
+            }
+            p[1].reverse();
+
+            // (step 4iv)
+            let y = u128::from_be_bytes(p[1]) % mu;
+
+            // (step 4v)
+            na = match which {
+                ffx::CipherType::Encrypt => (na + y) % mu,
+                ffx::CipherType::Decrypt => (na + mu - y) % mu,
+            };
+            // (step 4i, partial)
+            std::mem::swap(&mut mu, &mut mv);
+
+            // (step 4vii, 4viii; step 4vi is skipped)
+            std::mem::swap(&mut na, &mut nb);
+        }
+
+        if let ffx::CipherType::Decrypt = which {
+            std::mem::swap(&mut na, &mut nb);
+        }
+
+        // undo the split-half reversal and reassemble
+        let out = (reverse_bits(na, u) << v) | reverse_bits(nb, v);
+        Ok(out)
+    }
+
+    /// Encrypt a fixed-width bitstring held in the low `bit_len` bits
+    /// of `bits` (`bit_len` up to 128), bypassing `char`/`num_bigint`
+    /// conversion entirely.
+    ///
+    /// The context must have been constructed with radix 2.
+    pub fn encrypt_bits(
+        &self,
+        bits: u128,
+        bit_len: usize,
+        twk: Option<&[u8]>,
+    ) -> Result<u128> {
+        self.cipher_bits(bits, bit_len, twk, ffx::CipherType::Encrypt)
+    }
+
+    /// Decrypt a value produced by [`FF3_1::encrypt_bits`].
+    pub fn decrypt_bits(
+        &self,
+        bits: u128,
+        bit_len: usize,
+        twk: Option<&[u8]>,
+    ) -> Result<u128> {
+        self.cipher_bits(bits, bit_len, twk, ffx::CipherType::Decrypt)
+    }
+
     // common function to convert the input String to a sequence
     // of chars before the cipher operation and back again after
     fn cipher_string(
@@ -250,6 +684,146 @@ impl FF3_1 {
     pub fn decrypt(&self, ct: &str, twk: Option<&[u8]>) -> Result<String> {
         self.cipher_string(ct, twk, ffx::CipherType::Decrypt)
     }
+
+    /// Encrypt a string, binding the ciphertext to `associated_data`.
+    ///
+    /// The tweak is deterministically derived from `associated_data`
+    /// (e.g. a record id or field name) instead of being supplied
+    /// directly, so a caller can pass a domain string rather than
+    /// hand-building a 7-byte tweak, and two records encrypted with
+    /// different associated data get independent permutations.
+    /// Decrypting with the wrong associated data does not error (FPE
+    /// always produces a same-format output); it simply fails to
+    /// round-trip to the original plaintext.
+    pub fn encrypt_with_context(
+        &self,
+        pt: &str,
+        associated_data: &[u8],
+    ) -> Result<String> {
+        let twk = self.ffx.derive_tweak(associated_data, 7)?;
+        self.encrypt(pt, Some(&twk))
+    }
+
+    /// Decrypt a string, deriving the tweak from `associated_data` as
+    /// in [`FF3_1::encrypt_with_context`]. `associated_data` must
+    /// match what was used during encryption.
+    pub fn decrypt_with_context(
+        &self,
+        ct: &str,
+        associated_data: &[u8],
+    ) -> Result<String> {
+        let twk = self.ffx.derive_tweak(associated_data, 7)?;
+        self.decrypt(ct, Some(&twk))
+    }
+
+    // shared by the encrypt_u*/decrypt_u* family below: encode `value`
+    // as a `width`-digit numeral in the context's radix, run it
+    // through the normal char-based cipher, and decode the result
+    // back into an integer, so callers get type- and range-preserving
+    // encryption of surrogate keys/IDs without hand-rolling the
+    // string conversion and zero-padding themselves.
+    fn cipher_uint(
+        &self,
+        value: u128,
+        width: usize,
+        twk: Option<&[u8]>,
+        which: ffx::CipherType,
+    ) -> Result<u128> {
+        let radix = self.ffx.get_radix();
+        // the ciphertext is also a `width`-digit numeral, so it only
+        // converts back into a u128 when radix**width <= 2**128.
+        // `checked_pow` returns None past u128::MAX; the one such case
+        // that still fits is radix**width == 2**128 exactly
+        match (radix as u128).checked_pow(width as u32) {
+            Some(max) => {
+                if value >= max {
+                    return Err(Error::new(&format!(
+                        "value {} does not fit in {} digits of radix {}",
+                        value, width, radix
+                    )));
+                }
+            }
+            None if radix.is_power_of_two()
+                && radix.trailing_zeros() as usize * width == 128 => {}
+            None => {
+                return Err(Error::new(&format!(
+                    "{} digits of radix {} do not fit in a u128",
+                    width, radix
+                )));
+            }
+        }
+
+        let inp = self.ffx.bignum_to_chars(&value.into(), Some(width))?;
+        let out = self.cipher_chars(&inp, twk, which)?;
+        let n = self.ffx.chars_to_bignum(&out)?;
+
+        // n is guaranteed to be < radix**width <= 2**128 by the
+        // same domain check performed above, so this always succeeds
+        Ok(n.to_u128().unwrap())
+    }
+
+    /// Encrypt a `u32`, treating it as a `width`-digit numeral in this
+    /// context's radix (`value` must fit in `width` digits).
+    pub fn encrypt_u32(
+        &self,
+        value: u32,
+        width: usize,
+        twk: Option<&[u8]>,
+    ) -> Result<u32> {
+        Ok(self.cipher_uint(value as u128, width, twk, ffx::CipherType::Encrypt)? as u32)
+    }
+
+    /// Decrypt a value produced by [`FF3_1::encrypt_u32`].
+    pub fn decrypt_u32(
+        &self,
+        value: u32,
+        width: usize,
+        twk: Option<&[u8]>,
+    ) -> Result<u32> {
+        Ok(self.cipher_uint(value as u128, width, twk, ffx::CipherType::Decrypt)? as u32)
+    }
+
+    /// Encrypt a `u64`, treating it as a `width`-digit numeral in this
+    /// context's radix (`value` must fit in `width` digits).
+    pub fn encrypt_u64(
+        &self,
+        value: u64,
+        width: usize,
+        twk: Option<&[u8]>,
+    ) -> Result<u64> {
+        Ok(self.cipher_uint(value as u128, width, twk, ffx::CipherType::Encrypt)? as u64)
+    }
+
+    /// Decrypt a value produced by [`FF3_1::encrypt_u64`].
+    pub fn decrypt_u64(
+        &self,
+        value: u64,
+        width: usize,
+        twk: Option<&[u8]>,
+    ) -> Result<u64> {
+        Ok(self.cipher_uint(value as u128, width, twk, ffx::CipherType::Decrypt)? as u64)
+    }
+
+    /// Encrypt a `u128`, treating it as a `width`-digit numeral in
+    /// this context's radix (`value` must fit in `width` digits).
+    pub fn encrypt_u128(
+        &self,
+        value: u128,
+        width: usize,
+        twk: Option<&[u8]>,
+    ) -> Result<u128> {
+        self.cipher_uint(value, width, twk, ffx::CipherType::Encrypt)
+    }
+
+    /// Decrypt a value produced by [`FF3_1::encrypt_u128`].
+    pub fn decrypt_u128(
+        &self,
+        value: u128,
+        width: usize,
+        twk: Option<&[u8]>,
+    ) -> Result<u128> {
+        self.cipher_uint(value, width, twk, ffx::CipherType::Decrypt)
+    }
 }
 
 fn cipher(
@@ -300,6 +874,80 @@ mod tests {
         out
     }
 
+    #[test]
+    fn test_encrypt_bits_roundtrip() -> Result<()> {
+        let key = vec![0u8; 16];
+        let tweak = [0u8; 7];
+        let ff = FF3_1::new(&key, Some(&tweak), 2, None)?;
+
+        let pt: u128 = 0b1011_0110_1100_0101_1010;
+        let ct = ff.encrypt_bits(pt, 20, None)?;
+        assert_ne!(ct, pt);
+        assert_eq!(ff.decrypt_bits(ct, 20, None)?, pt);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_encrypt_bits_matches_generic_path() -> Result<()> {
+        let key = vec![0u8; 16];
+        let tweak = [0u8; 7];
+        let ff = FF3_1::new(&key, Some(&tweak), 2, None)?;
+
+        for &n in &[20usize, 33, 64, 96] {
+            let pt: u128 = 0x5a5a_5a5a_5a5a_5a5a_5a5a_5a5a_5a5a_5a5a
+                & ((1u128 << n) - 1);
+
+            let chars: Vec<char> = (0..n)
+                .rev()
+                .map(|i| {
+                    if (pt >> i) & 1 == 1 {
+                        '1'
+                    } else {
+                        '0'
+                    }
+                })
+                .collect();
+
+            let fast =
+                ff.cipher_chars_binary(&chars, None, super::ffx::CipherType::Encrypt)?;
+            let generic = ff.cipher_chars_generic(
+                &chars,
+                None,
+                super::ffx::CipherType::Encrypt,
+            )?;
+
+            assert_eq!(fast, generic, "mismatch at n={}", n);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_encrypt_u64_roundtrip() -> Result<()> {
+        let key = vec![0u8; 16];
+        let tweak = [0u8; 7];
+        let ff = FF3_1::new(&key, Some(&tweak), 10, None)?;
+
+        let pt: u64 = 123456789012;
+        let ct = ff.encrypt_u64(pt, 12, None)?;
+        assert_ne!(ct, pt);
+        assert_eq!(ff.decrypt_u64(ct, 12, None)?, pt);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_encrypt_u32_rejects_value_too_wide() -> Result<()> {
+        let key = vec![0u8; 16];
+        let tweak = [0u8; 7];
+        let ff = FF3_1::new(&key, Some(&tweak), 10, None)?;
+
+        assert!(ff.encrypt_u32(123456, 5, None).is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn test_kat_docstring() -> Result<()> {
         // Example from the module docstring
@@ -330,6 +978,89 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_new_with_cipher_roundtrip() -> Result<()> {
+        let key = vec![0u8; 16];
+        let tweak = [0u8; 7];
+        let ff = FF3_1::new_with_cipher(
+            crate::aes::CipherKind::Aria,
+            &key,
+            Some(&tweak),
+            10,
+            None,
+        )?;
+
+        let pt = "6520935496";
+        let ct = ff.encrypt(pt, None)?;
+        assert_eq!(ff.decrypt(&ct, None)?, pt);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_encrypt_into_matches_encrypt() -> Result<()> {
+        let key = vec![0u8; 16];
+        let tweak = [0u8; 7];
+        let ff = FF3_1::new(&key, Some(&tweak), 10, None)?;
+
+        let pt: Vec<char> = "6520935496".chars().collect();
+        let mut ct = vec!['\0'; pt.len()];
+        let mut scratch = vec!['\0'; ff.scratch_len(pt.len())];
+
+        ff.encrypt_into(&pt, &mut ct, &mut scratch, None)?;
+        assert_eq!(
+            String::from_iter(ct.clone()),
+            ff.encrypt(&String::from_iter(pt.clone()), None)?
+        );
+
+        let mut dt = vec!['\0'; pt.len()];
+        ff.decrypt_into(&ct, &mut dt, &mut scratch, None)?;
+        assert_eq!(dt, pt);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_encrypt_into_rejects_undersized_scratch() -> Result<()> {
+        let key = vec![0u8; 16];
+        let tweak = [0u8; 7];
+        let ff = FF3_1::new(&key, Some(&tweak), 10, None)?;
+
+        let pt: Vec<char> = "6520935496".chars().collect();
+        let mut ct = vec!['\0'; pt.len()];
+        let mut scratch = vec!['\0'; 1];
+
+        assert!(ff.encrypt_into(&pt, &mut ct, &mut scratch, None).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_with_rounds_roundtrip() -> Result<()> {
+        let key = vec![0u8; 16];
+        let tweak = [0u8; 7];
+        let ff = FF3_1::new(&key, Some(&tweak), 10, None)?.with_rounds(4);
+
+        let pt = "6520935496";
+        let ct = ff.encrypt(pt, None)?;
+        assert_eq!(ff.decrypt(&ct, None)?, pt);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_with_rounds_changes_ciphertext() -> Result<()> {
+        let key = vec![0u8; 16];
+        let tweak = [0u8; 7];
+        let standard = FF3_1::new(&key, Some(&tweak), 10, None)?;
+        let custom = FF3_1::new(&key, Some(&tweak), 10, None)?.with_rounds(4);
+
+        let pt = "6520935496";
+        assert_ne!(standard.encrypt(pt, None)?, custom.encrypt(pt, None)?);
+
+        Ok(())
+    }
+
     #[test]
     fn test_tweak_invalid_length() {
         let key = vec![0u8; 16];
@@ -338,6 +1069,33 @@ mod tests {
         assert!(res.is_err());
     }
 
+    #[test]
+    fn test_encrypt_with_context_roundtrip() -> Result<()> {
+        let key = vec![0u8; 16];
+        let ff = FF3_1::new(&key, None, 10, None)?;
+
+        let pt = "6520935496";
+        let ct = ff.encrypt_with_context(pt, b"customer-42")?;
+        assert_eq!(ff.decrypt_with_context(&ct, b"customer-42")?, pt);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_encrypt_with_context_binds_associated_data() -> Result<()> {
+        let key = vec![0u8; 16];
+        let ff = FF3_1::new(&key, None, 10, None)?;
+
+        let pt = "6520935496";
+        let ct_a = ff.encrypt_with_context(pt, b"customer-a")?;
+        let ct_b = ff.encrypt_with_context(pt, b"customer-b")?;
+        assert_ne!(ct_a, ct_b);
+
+        assert_ne!(ff.decrypt_with_context(&ct_a, b"customer-b")?, pt);
+
+        Ok(())
+    }
+
     #[test]
     fn test_alphabet_duplicates() {
         let key = vec![0u8; 16];
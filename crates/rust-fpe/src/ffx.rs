@@ -18,8 +18,17 @@ struct FFXSizeLimits {
     txt: SizeLimits,
 }
 
+/// Domain-separation label encrypted under the master key to derive
+/// `tweak_cipher`'s subkey (see [`FFX::new_with_cipher`] and
+/// [`FFX::derive_tweak`]). Exactly one block (16 bytes) so it can be
+/// fed straight into `Cipher::encrypt_block`, and the resulting output
+/// is a valid 16-byte key for every `CipherKind` (SM4 only accepts
+/// 16-byte keys; AES/ARIA/Camellia accept it too).
+const TWEAK_SUBKEY_LABEL: [u8; 16] = *b"fastfpe-tweak-kd";
+
 pub struct FFX {
     cipher: aes::Cipher,
+    tweak_cipher: aes::Cipher,
     twk: Vec<u8>,
     len: FFXSizeLimits,
     alpha: alphabet::Alphabet,
@@ -34,6 +43,33 @@ impl FFX {
         maxtwk: usize,
         radix: usize,
         opt_alpha: Option<&str>,
+    ) -> Result<Self> {
+        Self::new_with_cipher(
+            aes::CipherKind::Aes,
+            key,
+            opt_twk,
+            maxtxt,
+            mintwk,
+            maxtwk,
+            radix,
+            opt_alpha,
+        )
+    }
+
+    /// Same as [`FFX::new`], but backed by `kind` instead of always
+    /// AES. FFX only needs a keyed 128-bit-block cipher/PRF, so this
+    /// is the only place the backend choice is threaded in; nothing
+    /// downstream needs to know which cipher is in use.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_with_cipher(
+        kind: aes::CipherKind,
+        key: &[u8],
+        opt_twk: Option<&[u8]>,
+        maxtxt: usize,
+        mintwk: usize,
+        maxtwk: usize,
+        radix: usize,
+        opt_alpha: Option<&str>,
     ) -> Result<Self> {
         if radix < 2 {
             return Err(Error::new(&format!(
@@ -77,8 +113,19 @@ impl FFX {
             }
         }
 
+        let cipher = aes::Cipher::new_with_kind(kind, key)?;
+
+        // Derive an independent subkey for tweak derivation by
+        // encrypting a fixed domain-separation label under the master
+        // key, so `derive_tweak`'s CBC-MAC doesn't reuse the same key
+        // as the Feistel-round PRF.
+        let mut subkey = [0u8; 16];
+        cipher.encrypt_block(&TWEAK_SUBKEY_LABEL, &mut subkey);
+        let tweak_cipher = aes::Cipher::new_with_kind(kind, &subkey)?;
+
         Ok(FFX {
-            cipher: aes::Cipher::new(key)?,
+            cipher,
+            tweak_cipher,
 
             twk: twk,
 
@@ -108,6 +155,16 @@ impl FFX {
         self.alpha.len()
     }
 
+    /// Look up the numeral value of a single alphabet character.
+    pub fn alpha_ltr(&self, c: char) -> Result<usize> {
+        self.alpha.ltr(c)
+    }
+
+    /// Look up the alphabet character at a numeral position.
+    pub fn alpha_pos(&self, i: usize) -> Result<char> {
+        self.alpha.pos(i)
+    }
+
     #[allow(dead_code)]
     pub fn get_cipher_block_size(&self) -> usize {
         self.cipher.block_size()
@@ -150,19 +207,66 @@ impl FFX {
     }
 
     pub fn prf(&self, s: &[u8], d: &mut [u8]) -> Result<()> {
-        let mut c = self.cipher.clone();
-        let blksz = c.block_size();
+        self.context().prf_into(s, d)
+    }
 
-        for i in 0..(s.len() / blksz) {
+    pub fn ciph(&self, s: &[u8], d: &mut [u8]) -> Result<()> {
+        self.context().ciph_into(s, d)
+    }
+
+    /// Deterministically derive a tweak of `twklen` bytes from
+    /// `associated_data` (e.g. a record id, field name, or other
+    /// contextual string), so that two records with different
+    /// associated data are encrypted under independent permutations
+    /// and a ciphertext decrypted under the wrong context does not
+    /// round-trip to the original plaintext. No mismatch is detected:
+    /// decryption still succeeds and returns a same-format value.
+    ///
+    /// The derivation runs `associated_data` (PKCS#7-padded to the
+    /// cipher's block size) through a CBC-MAC keyed by `tweak_cipher`
+    /// - a subkey split from the master key (see
+    /// [`FFX::new_with_cipher`]), independent of the one `prf`/`ciph`
+    /// use for the Feistel rounds, so this derivation can't be
+    /// leveraged against the round PRF or vice versa. The resulting
+    /// MAC block is repeated to fill `twklen` bytes.
+    pub fn derive_tweak(
+        &self,
+        associated_data: &[u8],
+        twklen: usize,
+    ) -> Result<Vec<u8>> {
+        let blksz = self.tweak_cipher.block_size();
+
+        let mut padded = associated_data.to_vec();
+        let pad = blksz - (padded.len() % blksz);
+        padded.resize(padded.len() + pad, pad as u8);
+
+        let mut chain = [0u8; 16];
+        let mut mac = vec![0u8; blksz];
+        for i in 0..(padded.len() / blksz) {
             let j = i * blksz;
-            c.encrypt_block(&s[j..(j + blksz)], d);
+            for k in 0..blksz {
+                chain[k] ^= padded[j + k];
+            }
+            self.tweak_cipher.encrypt_block(&chain[..blksz], &mut mac);
+            chain[..blksz].copy_from_slice(&mac[..blksz]);
         }
 
-        Ok(())
+        let mut tweak = vec![0u8; twklen];
+        for (i, b) in tweak.iter_mut().enumerate() {
+            *b = mac[i % blksz];
+        }
+
+        Ok(tweak)
     }
 
-    pub fn ciph(&self, s: &[u8], d: &mut [u8]) -> Result<()> {
-        self.prf(&s[0..16], d)
+    /// Build a reusable [`FfxContext`] bound to this `FFX` instance.
+    ///
+    /// The returned context owns a cloned, already key-scheduled cipher
+    /// and scratch buffers that can be reused across many encrypt/decrypt
+    /// calls, avoiding the per-call cipher clone and digit-vector
+    /// allocation that `prf`/`ciph`/`chars_to_bignum` otherwise pay.
+    pub fn context(&self) -> FfxContext<'_> {
+        FfxContext::new(self)
     }
 
     pub fn chars_to_bignum(
@@ -210,6 +314,87 @@ impl FFX {
     }
 }
 
+/// A reusable encryption context bound to an [`FFX`] instance.
+///
+/// `FFX::prf`/`FFX::ciph` clone the key-scheduled cipher, and
+/// `FFX::chars_to_bignum` allocates a fresh digit buffer, on every
+/// call, which shows up in profiles for
+/// workloads that encrypt millions of values. `FfxContext` hoists those
+/// allocations out of the hot loop: it owns a cloned cipher (the key
+/// schedule is computed once, at construction) and a scratch digit
+/// buffer that `*_into` methods reuse across calls instead of
+/// reallocating.
+///
+/// Build one with [`FFX::context`] and reuse it for the lifetime of a
+/// batch of operations.
+pub struct FfxContext<'a> {
+    ffx: &'a FFX,
+    cipher: aes::Cipher,
+    digits: Vec<u8>,
+}
+
+impl<'a> FfxContext<'a> {
+    fn new(ffx: &'a FFX) -> Self {
+        FfxContext {
+            ffx,
+            cipher: ffx.cipher.clone(),
+            digits: Vec::new(),
+        }
+    }
+
+    /// Allocation-free equivalent of [`FFX::prf`].
+    ///
+    /// `Cipher` only does single-block, unchained encryption (see its
+    /// doc comment), so the CBC-MAC chaining - each block XORed with
+    /// the previous block's ciphertext before encrypting, starting
+    /// from a zero IV - is done explicitly here instead of relying on
+    /// a stateful CBC mode object.
+    pub fn prf_into(&mut self, s: &[u8], d: &mut [u8]) -> Result<()> {
+        let blksz = self.cipher.block_size();
+        let mut chain = [0u8; 16];
+
+        for i in 0..(s.len() / blksz) {
+            let j = i * blksz;
+            for k in 0..blksz {
+                chain[k] ^= s[j + k];
+            }
+            self.cipher.encrypt_block(&chain[..blksz], d);
+            chain[..blksz].copy_from_slice(&d[..blksz]);
+        }
+
+        Ok(())
+    }
+
+    /// Allocation-free equivalent of [`FFX::ciph`].
+    pub fn ciph_into(&mut self, s: &[u8], d: &mut [u8]) -> Result<()> {
+        self.prf_into(&s[0..16], d)
+    }
+
+    /// Allocation-free equivalent of [`FFX::chars_to_bignum`].
+    ///
+    /// The digit scratch buffer owned by this context is cleared and
+    /// reused on every call, instead of allocating a fresh `Vec<u8>`.
+    pub fn chars_to_bignum(
+        &mut self,
+        chars: &[char],
+    ) -> Result<num_bigint::BigInt> {
+        let radix = self.ffx.alpha.len();
+
+        self.digits.clear();
+        self.digits.reserve(chars.len());
+        for c in chars {
+            self.digits.push(self.ffx.alpha.ltr(*c)? as u8);
+        }
+
+        Ok(num_bigint::BigInt::from_radix_be(
+            num_bigint::Sign::Plus,
+            &self.digits,
+            radix as u32,
+        )
+        .unwrap())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::FFX;
@@ -217,6 +402,21 @@ mod tests {
 
     use std::str::FromStr;
 
+    #[test]
+    fn test_ffx_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<FFX>();
+    }
+
+    #[test]
+    fn test_domain_below_nist_floor_is_rejected() {
+        // radix 10 requires at least 6 digits for radix**minlen >=
+        // 1_000_000 (the NIST-mandated domain-size floor); a maxtxt
+        // below that minimum length must be rejected
+        let res = FFX::new(&[0; 16], None, 3, 0, 0, 10, None);
+        assert!(res.is_err());
+    }
+
     #[test]
     fn test_cipher_reuse() -> Result<()> {
         let exp = [
@@ -238,6 +438,65 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_context_matches_direct_calls() -> Result<()> {
+        let ffx = FFX::new(&[0; 16], None, 1024, 0, 0, 10, None)?;
+
+        let s: [u8; 16] = [0; 16];
+        let mut d1: [u8; 16] = [0; 16];
+        let mut d2: [u8; 16] = [0; 16];
+
+        ffx.ciph(&s, &mut d1)?;
+
+        let mut ctx = ffx.context();
+        ctx.ciph_into(&s, &mut d2)?;
+
+        assert!(d1 == d2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_derive_tweak_is_deterministic_and_context_bound() -> Result<()> {
+        let ffx = FFX::new(&[0; 16], None, 1024, 0, 0, 10, None)?;
+
+        let t1 = ffx.derive_tweak(b"customer-1", 7)?;
+        let t2 = ffx.derive_tweak(b"customer-1", 7)?;
+        let t3 = ffx.derive_tweak(b"customer-2", 7)?;
+
+        assert_eq!(t1.len(), 7);
+        assert_eq!(t1, t2);
+        assert_ne!(t1, t3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_new_with_cipher_selects_backend() -> Result<()> {
+        let aes = FFX::new(&[0; 16], None, 1024, 0, 0, 10, None)?;
+        let sm4 = FFX::new_with_cipher(
+            crate::aes::CipherKind::Sm4,
+            &[0; 16],
+            None,
+            1024,
+            0,
+            0,
+            10,
+            None,
+        )?;
+
+        let s: [u8; 16] = [0; 16];
+        let mut d1: [u8; 16] = [0; 16];
+        let mut d2: [u8; 16] = [0; 16];
+
+        aes.ciph(&s, &mut d1)?;
+        sm4.ciph(&s, &mut d2)?;
+
+        assert_ne!(d1, d2);
+
+        Ok(())
+    }
+
     #[test]
     fn test_bignum_conversion() -> Result<()> {
         let ffx = FFX::new(&[0; 16], None, 1024, 0, 0, 10, None)?;
@@ -0,0 +1,169 @@
+//! Passphrase-based key derivation
+//!
+//! `FFX::new` (and therefore the FF1/FF3-1 constructors) requires a raw
+//! AES key of exactly 16, 24, or 32 bytes. Callers who only have a
+//! human passphrase end up inventing their own way to turn it into a
+//! key of the right size, which is usually some insecure padding or
+//! truncation scheme. This module derives keys from passphrases using
+//! a memory-hard KDF instead, mirroring the approach libsodium takes
+//! in `crypto_pwhash`: Argon2id by default, with scrypt available for
+//! interop with systems that already standardized on it.
+//!
+//! Pulls in the `argon2` and `scrypt` crates; this source tree ships
+//! without a Cargo.toml, so those two need to be added to the
+//! manifest alongside this module landing.
+
+use crate::error::Error;
+use crate::result::Result;
+
+/// Which memory-hard KDF to use when deriving a key from a passphrase.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kdf {
+    /// Argon2id, the variant recommended for password hashing by the
+    /// Argon2 RFC.
+    Argon2id,
+    /// scrypt, for interop with systems that already standardized on
+    /// it.
+    Scrypt,
+}
+
+/// Tunable cost parameters for the chosen KDF.
+///
+/// The defaults mirror the minimums recommended by the OWASP password
+/// storage cheat sheet for Argon2id. Callers deriving many keys in a
+/// memory-constrained environment may need to lower `mem_cost_kib`, at
+/// the cost of making the derivation cheaper to brute-force.
+#[derive(Clone, Copy, Debug)]
+pub struct KdfParams {
+    pub kdf: Kdf,
+    pub mem_cost_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        KdfParams {
+            kdf: Kdf::Argon2id,
+            mem_cost_kib: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+        }
+    }
+}
+
+/// Derive an AES key of `key_len` bytes (16, 24, or 32) from
+/// `passphrase` and `salt` using the configured KDF.
+///
+/// The same `(passphrase, salt, params, key_len)` always derives the
+/// same key, so the salt must be stored alongside the ciphertext for
+/// decryption to reproduce it; unlike the passphrase, it does not need
+/// to be kept secret.
+pub fn derive_key(
+    passphrase: &[u8],
+    salt: &[u8],
+    key_len: usize,
+    params: &KdfParams,
+) -> Result<Vec<u8>> {
+    if key_len != 16 && key_len != 24 && key_len != 32 {
+        return Err(Error::new(&format!(
+            "invalid key length; must be 16, 24, or 32 bytes, got {}",
+            key_len
+        )));
+    }
+
+    let mut key = vec![0u8; key_len];
+
+    match params.kdf {
+        Kdf::Argon2id => {
+            use argon2::{Algorithm, Argon2, Params, Version};
+
+            let argon2_params = Params::new(
+                params.mem_cost_kib,
+                params.iterations,
+                params.parallelism,
+                Some(key_len),
+            )
+            .map_err(|e| {
+                Error::new(&format!("invalid argon2 parameters: {}", e))
+            })?;
+
+            let argon2 = Argon2::new(
+                Algorithm::Argon2id,
+                Version::V0x13,
+                argon2_params,
+            );
+            argon2
+                .hash_password_into(passphrase, salt, &mut key)
+                .map_err(|e| {
+                    Error::new(&format!("argon2 derivation failed: {}", e))
+                })?;
+        }
+        Kdf::Scrypt => {
+            use scrypt::{scrypt, Params};
+
+            // map the shared mem_cost_kib knob onto scrypt's log2(N)
+            // cost parameter, clamped to a sane range
+            let log_n = (31 - params.mem_cost_kib.max(1).leading_zeros())
+                .clamp(10, 20) as u8;
+            let scrypt_params =
+                Params::new(log_n, 8, params.parallelism, key_len)
+                    .map_err(|e| {
+                        Error::new(&format!(
+                            "invalid scrypt parameters: {}",
+                            e
+                        ))
+                    })?;
+
+            scrypt(passphrase, salt, &scrypt_params, &mut key).map_err(
+                |e| Error::new(&format!("scrypt derivation failed: {}", e)),
+            )?;
+        }
+    }
+
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{derive_key, Kdf, KdfParams};
+
+    #[test]
+    fn deterministic_for_same_inputs() {
+        let params = KdfParams::default();
+        let k1 =
+            derive_key(b"correct horse battery staple", b"somesalt", 16, &params)
+                .unwrap();
+        let k2 =
+            derive_key(b"correct horse battery staple", b"somesalt", 16, &params)
+                .unwrap();
+        assert_eq!(k1, k2);
+        assert_eq!(k1.len(), 16);
+    }
+
+    #[test]
+    fn different_salt_different_key() {
+        let params = KdfParams::default();
+        let k1 = derive_key(b"passphrase", b"salt-one", 16, &params).unwrap();
+        let k2 = derive_key(b"passphrase", b"salt-two", 16, &params).unwrap();
+        assert_ne!(k1, k2);
+    }
+
+    #[test]
+    fn scrypt_backend_produces_requested_length() {
+        let params = KdfParams {
+            kdf: Kdf::Scrypt,
+            mem_cost_kib: 16 * 1024,
+            iterations: 1,
+            parallelism: 1,
+        };
+        let key = derive_key(b"passphrase", b"salt", 32, &params).unwrap();
+        assert_eq!(key.len(), 32);
+    }
+
+    #[test]
+    fn rejects_invalid_key_length() {
+        let params = KdfParams::default();
+        assert!(derive_key(b"passphrase", b"salt", 20, &params).is_err());
+    }
+}
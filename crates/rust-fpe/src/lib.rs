@@ -33,11 +33,13 @@
 //! assert!(out == pt);
 //! ```
 
-pub(crate) mod aes;
+pub mod aes;
 pub(crate) mod alphabet;
 pub mod ff1;
 pub mod ff3_1;
 pub(crate) mod ffx;
+pub mod kdf;
+pub(crate) mod limbs;
 
 /// Errors returned by the FPE library
 pub mod error {
@@ -1,4 +1,5 @@
 use fpe::ff1;
+use fpe::kdf::KdfParams;
 
 pub fn encrypt(key: &str, tweak: &str, alphabet: &str, plaintext: &str) -> Result<String, String> {
     let key_bytes = hex::decode(key).map_err(|e| format!("Invalid key hex: {e}"))?;
@@ -38,10 +39,137 @@ pub fn decrypt(key: &str, tweak: &str, alphabet: &str, ciphertext: &str) -> Resu
     .map_err(|e| format!("Decryption failed: {e}"))
 }
 
+pub fn encrypt_with_passphrase(
+    passphrase: &str,
+    salt: &str,
+    key_len: usize,
+    tweak: &str,
+    alphabet: &str,
+    plaintext: &str,
+) -> Result<String, String> {
+    let tweak_bytes = if tweak.is_empty() {
+        vec![]
+    } else {
+        hex::decode(tweak).map_err(|e| format!("Invalid tweak hex: {e}"))?
+    };
+    let radix = alphabet.chars().count();
+
+    let ff1 = ff1::FF1::from_passphrase(
+        passphrase.as_bytes(),
+        salt.as_bytes(),
+        key_len,
+        &KdfParams::default(),
+        None,
+        0,
+        0,
+        radix,
+        Some(alphabet),
+    )
+    .map_err(|e| format!("Key derivation failed: {e}"))?;
+
+    ff1.encrypt(plaintext, Some(&tweak_bytes))
+        .map_err(|e| format!("Encryption failed: {e}"))
+}
+
+pub fn decrypt_with_passphrase(
+    passphrase: &str,
+    salt: &str,
+    key_len: usize,
+    tweak: &str,
+    alphabet: &str,
+    ciphertext: &str,
+) -> Result<String, String> {
+    let tweak_bytes = if tweak.is_empty() {
+        vec![]
+    } else {
+        hex::decode(tweak).map_err(|e| format!("Invalid tweak hex: {e}"))?
+    };
+    let radix = alphabet.chars().count();
+
+    let ff1 = ff1::FF1::from_passphrase(
+        passphrase.as_bytes(),
+        salt.as_bytes(),
+        key_len,
+        &KdfParams::default(),
+        None,
+        0,
+        0,
+        radix,
+        Some(alphabet),
+    )
+    .map_err(|e| format!("Key derivation failed: {e}"))?;
+
+    ff1.decrypt(ciphertext, Some(&tweak_bytes))
+        .map_err(|e| format!("Decryption failed: {e}"))
+}
+
+pub fn encrypt_with_context(
+    key: &str,
+    associated_data: &str,
+    alphabet: &str,
+    plaintext: &str,
+) -> Result<String, String> {
+    let key_bytes = hex::decode(key).map_err(|e| format!("Invalid key hex: {e}"))?;
+    let radix = alphabet.chars().count();
+
+    let ff1 = ff1::FF1::new(&key_bytes, None, 0, 0, radix, Some(alphabet))
+        .map_err(|e| format!("Context creation failed: {e}"))?;
+
+    ff1.encrypt_with_context(plaintext, associated_data.as_bytes())
+        .map_err(|e| format!("Encryption failed: {e}"))
+}
+
+pub fn decrypt_with_context(
+    key: &str,
+    associated_data: &str,
+    alphabet: &str,
+    ciphertext: &str,
+) -> Result<String, String> {
+    let key_bytes = hex::decode(key).map_err(|e| format!("Invalid key hex: {e}"))?;
+    let radix = alphabet.chars().count();
+
+    let ff1 = ff1::FF1::new(&key_bytes, None, 0, 0, radix, Some(alphabet))
+        .map_err(|e| format!("Context creation failed: {e}"))?;
+
+    ff1.decrypt_with_context(ciphertext, associated_data.as_bytes())
+        .map_err(|e| format!("Decryption failed: {e}"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn passphrase_roundtrip() {
+        let tweak = "00010203";
+        let alphabet = "0123456789";
+        let pt = "0123456789";
+
+        let ct =
+            encrypt_with_passphrase("correct horse battery staple", "somesalt", 16, tweak, alphabet, pt)
+                .unwrap();
+        assert_eq!(
+            decrypt_with_passphrase("correct horse battery staple", "somesalt", 16, tweak, alphabet, &ct)
+                .unwrap(),
+            pt
+        );
+    }
+
+    #[test]
+    fn wrong_passphrase_does_not_decrypt_correctly() {
+        let tweak = "00010203";
+        let alphabet = "0123456789";
+        let pt = "0123456789";
+
+        let ct =
+            encrypt_with_passphrase("correct horse battery staple", "somesalt", 16, tweak, alphabet, pt)
+                .unwrap();
+        let dt =
+            decrypt_with_passphrase("wrong passphrase", "somesalt", 16, tweak, alphabet, &ct)
+                .unwrap();
+        assert_ne!(dt, pt);
+    }
+
     #[test]
     fn reference_vector() {
         let key = "2b7e151628aed2a6abf7158809cf4f3c"; // 128-bit
@@ -105,4 +233,20 @@ mod tests {
         let ct = encrypt(key, &long_tweak, alphabet, pt).unwrap();
         assert_eq!(decrypt(key, &long_tweak, alphabet, &ct).unwrap(), pt);
     }
+
+    #[test]
+    fn with_context_roundtrip() {
+        let key = "2b7e151628aed2a6abf7158809cf4f3c";
+        let alphabet = "0123456789";
+        let pt = "0123456789";
+
+        let ct = encrypt_with_context(key, "customer-42", alphabet, pt).unwrap();
+        assert_eq!(
+            decrypt_with_context(key, "customer-42", alphabet, &ct).unwrap(),
+            pt
+        );
+
+        let other_ct = encrypt_with_context(key, "customer-43", alphabet, pt).unwrap();
+        assert_ne!(ct, other_ct);
+    }
 }
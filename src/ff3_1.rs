@@ -1,4 +1,5 @@
 use fpe::ff3_1;
+use fpe::kdf::KdfParams;
 
 pub fn encrypt(key: &str, tweak: &str, alphabet: &str, plaintext: &str) -> Result<String, String> {
     let key_bytes = hex::decode(key).map_err(|e| format!("Invalid key hex: {}", e))?;
@@ -30,6 +31,94 @@ pub fn decrypt(key: &str, tweak: &str, alphabet: &str, ciphertext: &str) -> Resu
     .map_err(|e| format!("{:?}", e))
 }
 
+pub fn encrypt_with_passphrase(
+    passphrase: &str,
+    salt: &str,
+    key_len: usize,
+    tweak: &str,
+    alphabet: &str,
+    plaintext: &str,
+) -> Result<String, String> {
+    let tweak_bytes = hex::decode(tweak).map_err(|e| format!("Invalid tweak hex: {}", e))?;
+    let radix = alphabet.len();
+
+    let ff3_1 = ff3_1::FF3_1::from_passphrase(
+        passphrase.as_bytes(),
+        salt.as_bytes(),
+        key_len,
+        &KdfParams::default(),
+        None,
+        radix,
+        Some(alphabet),
+    )
+    .map_err(|e| format!("{:?}", e))?;
+
+    ff3_1
+        .encrypt(plaintext, Some(&tweak_bytes))
+        .map_err(|e| format!("{:?}", e))
+}
+
+pub fn decrypt_with_passphrase(
+    passphrase: &str,
+    salt: &str,
+    key_len: usize,
+    tweak: &str,
+    alphabet: &str,
+    ciphertext: &str,
+) -> Result<String, String> {
+    let tweak_bytes = hex::decode(tweak).map_err(|e| format!("Invalid tweak hex: {}", e))?;
+    let radix = alphabet.len();
+
+    let ff3_1 = ff3_1::FF3_1::from_passphrase(
+        passphrase.as_bytes(),
+        salt.as_bytes(),
+        key_len,
+        &KdfParams::default(),
+        None,
+        radix,
+        Some(alphabet),
+    )
+    .map_err(|e| format!("{:?}", e))?;
+
+    ff3_1
+        .decrypt(ciphertext, Some(&tweak_bytes))
+        .map_err(|e| format!("{:?}", e))
+}
+
+pub fn encrypt_with_context(
+    key: &str,
+    associated_data: &str,
+    alphabet: &str,
+    plaintext: &str,
+) -> Result<String, String> {
+    let key_bytes = hex::decode(key).map_err(|e| format!("Invalid key hex: {}", e))?;
+    let radix = alphabet.len();
+
+    let ff3_1 = ff3_1::FF3_1::new(&key_bytes, None, radix, Some(alphabet))
+        .map_err(|e| format!("{:?}", e))?;
+
+    ff3_1
+        .encrypt_with_context(plaintext, associated_data.as_bytes())
+        .map_err(|e| format!("{:?}", e))
+}
+
+pub fn decrypt_with_context(
+    key: &str,
+    associated_data: &str,
+    alphabet: &str,
+    ciphertext: &str,
+) -> Result<String, String> {
+    let key_bytes = hex::decode(key).map_err(|e| format!("Invalid key hex: {}", e))?;
+    let radix = alphabet.len();
+
+    let ff3_1 = ff3_1::FF3_1::new(&key_bytes, None, radix, Some(alphabet))
+        .map_err(|e| format!("{:?}", e))?;
+
+    ff3_1
+        .decrypt_with_context(ciphertext, associated_data.as_bytes())
+        .map_err(|e| format!("{:?}", e))
+}
+
 #[test]
 fn test_ff3_1_reference() {
     let key = "00112233445566778899aabbccddeeff";
@@ -43,3 +132,19 @@ fn test_ff3_1_reference() {
     assert_eq!(ciphertext, "cf64ccfe");
     assert_eq!(decrypted, plaintext);
 }
+
+#[test]
+fn test_ff3_1_with_context_roundtrip() {
+    let key = "00112233445566778899aabbccddeeff";
+    let alphabet = "abcdef0123456789";
+    let plaintext = "12345678";
+
+    let ciphertext = encrypt_with_context(key, "customer-42", alphabet, plaintext).unwrap();
+    assert_eq!(
+        decrypt_with_context(key, "customer-42", alphabet, &ciphertext).unwrap(),
+        plaintext
+    );
+
+    let other_ciphertext = encrypt_with_context(key, "customer-43", alphabet, plaintext).unwrap();
+    assert_ne!(ciphertext, other_ciphertext);
+}
@@ -57,13 +57,141 @@ fn ff3_1_decrypt(
     })
 }
 
+#[pyfunction]
+#[pyo3(signature = (passphrase, salt, key_len, tweak, alphabet, plaintext))]
+/// Encrypts plaintext using FF3-1, deriving the AES key from a passphrase
+///
+/// Args:
+///     passphrase (str): Human passphrase the key is derived from
+///     salt (str): Salt used by the key derivation; store alongside the ciphertext
+///     key_len (int): Desired AES key length in bytes (16, 24, or 32)
+///     tweak (str): Hex-encoded tweak (exactly 7 bytes after decoding)
+///     alphabet (str): String containing the valid characters
+///     plaintext (str): Text to encrypt, must contain only characters from alphabet
+///
+/// Returns:
+///     str: The encrypted text
+///
+/// Raises:
+///     ValueError: If inputs are invalid
+fn ff3_1_encrypt_with_passphrase(
+    py: Python,
+    passphrase: &str,
+    salt: &str,
+    key_len: usize,
+    tweak: &str,
+    alphabet: &str,
+    plaintext: &str,
+) -> PyResult<String> {
+    py.allow_threads(|| {
+        ff3_1::encrypt_with_passphrase(passphrase, salt, key_len, tweak, alphabet, plaintext)
+            .map_err(|e| PyValueError::new_err(e))
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (passphrase, salt, key_len, tweak, alphabet, ciphertext))]
+/// Decrypts ciphertext using FF3-1, deriving the AES key from a passphrase
+///
+/// Args:
+///     passphrase (str): Human passphrase the key is derived from
+///     salt (str): Salt used by the key derivation; must match the one used to encrypt
+///     key_len (int): Desired AES key length in bytes (16, 24, or 32)
+///     tweak (str): Hex-encoded tweak (exactly 7 bytes after decoding)
+///     alphabet (str): String containing the valid characters
+///     ciphertext (str): Text to decrypt, must contain only characters from alphabet
+///
+/// Returns:
+///     str: The decrypted text
+///
+/// Raises:
+///     ValueError: If inputs are invalid
+fn ff3_1_decrypt_with_passphrase(
+    py: Python,
+    passphrase: &str,
+    salt: &str,
+    key_len: usize,
+    tweak: &str,
+    alphabet: &str,
+    ciphertext: &str,
+) -> PyResult<String> {
+    py.allow_threads(|| {
+        ff3_1::decrypt_with_passphrase(passphrase, salt, key_len, tweak, alphabet, ciphertext)
+            .map_err(|e| PyValueError::new_err(e))
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (key, associated_data, alphabet, plaintext))]
+/// Encrypts plaintext using FF3-1, deriving the tweak from associated data
+///
+/// Args:
+///     key (str): Hex-encoded AES key (16, 24, or 32 bytes after decoding)
+///     associated_data (str): Contextual string (e.g. a record id) the tweak is derived from
+///     alphabet (str): String containing the valid characters
+///     plaintext (str): Text to encrypt, must contain only characters from alphabet
+///
+/// Returns:
+///     str: The encrypted text
+///
+/// Raises:
+///     ValueError: If inputs are invalid
+fn ff3_1_encrypt_with_context(
+    py: Python,
+    key: &str,
+    associated_data: &str,
+    alphabet: &str,
+    plaintext: &str,
+) -> PyResult<String> {
+    py.allow_threads(|| {
+        ff3_1::encrypt_with_context(key, associated_data, alphabet, plaintext)
+            .map_err(|e| PyValueError::new_err(e))
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (key, associated_data, alphabet, ciphertext))]
+/// Decrypts ciphertext using FF3-1, deriving the tweak from associated data
+///
+/// Args:
+///     key (str): Hex-encoded AES key (16, 24, or 32 bytes after decoding)
+///     associated_data (str): Contextual string the tweak is derived from; must match encryption
+///     alphabet (str): String containing the valid characters
+///     ciphertext (str): Text to decrypt, must contain only characters from alphabet
+///
+/// Returns:
+///     str: The decrypted text
+///
+/// Raises:
+///     ValueError: If inputs are invalid
+fn ff3_1_decrypt_with_context(
+    py: Python,
+    key: &str,
+    associated_data: &str,
+    alphabet: &str,
+    ciphertext: &str,
+) -> PyResult<String> {
+    py.allow_threads(|| {
+        ff3_1::decrypt_with_context(key, associated_data, alphabet, ciphertext)
+            .map_err(|e| PyValueError::new_err(e))
+    })
+}
+
 #[pymodule]
 fn _rust_fastfpe(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(ff3_1_encrypt, m)?)?;
     m.add_function(wrap_pyfunction!(ff3_1_decrypt, m)?)?;
+    m.add_function(wrap_pyfunction!(ff3_1_encrypt_with_passphrase, m)?)?;
+    m.add_function(wrap_pyfunction!(ff3_1_decrypt_with_passphrase, m)?)?;
+    m.add_function(wrap_pyfunction!(ff3_1_encrypt_with_context, m)?)?;
+    m.add_function(wrap_pyfunction!(ff3_1_decrypt_with_context, m)?)?;
     // FF1 bindings
     m.add_function(wrap_pyfunction!(ff1_encrypt, m)?)?;
     m.add_function(wrap_pyfunction!(ff1_decrypt, m)?)?;
+    m.add_function(wrap_pyfunction!(ff1_encrypt_with_passphrase, m)?)?;
+    m.add_function(wrap_pyfunction!(ff1_decrypt_with_passphrase, m)?)?;
+    m.add_function(wrap_pyfunction!(ff1_encrypt_with_context, m)?)?;
+    m.add_function(wrap_pyfunction!(ff1_decrypt_with_context, m)?)?;
     Ok(())
 }
 
@@ -115,3 +243,115 @@ fn ff1_decrypt(
         ff1::decrypt(key, tweak, alphabet, ciphertext).map_err(|e| PyValueError::new_err(e))
     })
 }
+
+#[pyfunction]
+#[pyo3(signature = (passphrase, salt, key_len, tweak, alphabet, plaintext))]
+/// Encrypts plaintext using FF1, deriving the AES key from a passphrase
+///
+/// Args:
+///     passphrase (str): Human passphrase the key is derived from
+///     salt (str): Salt used by the key derivation; store alongside the ciphertext
+///     key_len (int): Desired AES key length in bytes (16, 24, or 32)
+///     tweak (str): Hex-encoded tweak (may be empty)
+///     alphabet (str): String containing the valid characters (must have at least radix unique chars)
+///     plaintext (str): Text to encrypt, must contain only characters from alphabet
+/// Returns:
+///     str: The encrypted text
+/// Raises:
+///     ValueError: If inputs are invalid
+fn ff1_encrypt_with_passphrase(
+    py: Python,
+    passphrase: &str,
+    salt: &str,
+    key_len: usize,
+    tweak: &str,
+    alphabet: &str,
+    plaintext: &str,
+) -> PyResult<String> {
+    py.allow_threads(|| {
+        ff1::encrypt_with_passphrase(passphrase, salt, key_len, tweak, alphabet, plaintext)
+            .map_err(|e| PyValueError::new_err(e))
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (passphrase, salt, key_len, tweak, alphabet, ciphertext))]
+/// Decrypts ciphertext using FF1, deriving the AES key from a passphrase
+///
+/// Args:
+///     passphrase (str): Human passphrase the key is derived from
+///     salt (str): Salt used by the key derivation; must match the one used to encrypt
+///     key_len (int): Desired AES key length in bytes (16, 24, or 32)
+///     tweak (str): Hex-encoded tweak (may be empty)
+///     alphabet (str): String containing the valid characters (must have at least radix unique chars)
+///     ciphertext (str): Text to decrypt, must contain only characters from alphabet
+/// Returns:
+///     str: The decrypted text
+/// Raises:
+///     ValueError: If inputs are invalid
+fn ff1_decrypt_with_passphrase(
+    py: Python,
+    passphrase: &str,
+    salt: &str,
+    key_len: usize,
+    tweak: &str,
+    alphabet: &str,
+    ciphertext: &str,
+) -> PyResult<String> {
+    py.allow_threads(|| {
+        ff1::decrypt_with_passphrase(passphrase, salt, key_len, tweak, alphabet, ciphertext)
+            .map_err(|e| PyValueError::new_err(e))
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (key, associated_data, alphabet, plaintext))]
+/// Encrypts plaintext using FF1, deriving the tweak from associated data
+///
+/// Args:
+///     key (str): Hex-encoded AES key (16, 24, or 32 bytes after decoding)
+///     associated_data (str): Contextual string (e.g. a record id) the tweak is derived from
+///     alphabet (str): String containing the valid characters (must have at least radix unique chars)
+///     plaintext (str): Text to encrypt, must contain only characters from alphabet
+/// Returns:
+///     str: The encrypted text
+/// Raises:
+///     ValueError: If inputs are invalid
+fn ff1_encrypt_with_context(
+    py: Python,
+    key: &str,
+    associated_data: &str,
+    alphabet: &str,
+    plaintext: &str,
+) -> PyResult<String> {
+    py.allow_threads(|| {
+        ff1::encrypt_with_context(key, associated_data, alphabet, plaintext)
+            .map_err(|e| PyValueError::new_err(e))
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (key, associated_data, alphabet, ciphertext))]
+/// Decrypts ciphertext using FF1, deriving the tweak from associated data
+///
+/// Args:
+///     key (str): Hex-encoded AES key (16, 24, or 32 bytes after decoding)
+///     associated_data (str): Contextual string the tweak is derived from; must match encryption
+///     alphabet (str): String containing the valid characters (must have at least radix unique chars)
+///     ciphertext (str): Text to decrypt, must contain only characters from alphabet
+/// Returns:
+///     str: The decrypted text
+/// Raises:
+///     ValueError: If inputs are invalid
+fn ff1_decrypt_with_context(
+    py: Python,
+    key: &str,
+    associated_data: &str,
+    alphabet: &str,
+    ciphertext: &str,
+) -> PyResult<String> {
+    py.allow_threads(|| {
+        ff1::decrypt_with_context(key, associated_data, alphabet, ciphertext)
+            .map_err(|e| PyValueError::new_err(e))
+    })
+}